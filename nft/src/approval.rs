@@ -0,0 +1,144 @@
+use crate::external::ext_approval_receiver;
+use crate::*;
+use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+use near_contract_standards::non_fungible_token::bytes_for_approved_account_id;
+use near_sdk::assert_one_yocto;
+
+const GAS_FOR_NFT_APPROVE: Gas = 10_000_000_000_000;
+const NO_DEPOSIT: Balance = 0;
+
+/// NEP-178 approval management. Same behavior as `NonFungibleToken`, except that registered
+/// owners pay for approvals from their storage balance and get revoked approvals credited back.
+/// Soulbound tokens can't be approved.
+#[near_bindgen]
+impl NonFungibleTokenApproval for Contract {
+    #[payable]
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: ValidAccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(&env::predecessor_account_id(), &owner_id, "Predecessor must be token owner.");
+        self.assert_not_soulbound(&token_id);
+
+        let account_id: AccountId = account_id.into();
+        // the auction holds the contract's approval until it's settled
+        assert!(
+            account_id != env::current_account_id() || self.auctions.get(&token_id).is_none(),
+            "Token is on auction"
+        );
+        let (approval_id, storage_used) = self.internal_approve(&token_id, &account_id);
+        self.internal_charge_storage(&owner_id, storage_used);
+
+        msg.map(|msg| {
+            ext_approval_receiver::nft_on_approve(
+                token_id,
+                owner_id,
+                approval_id,
+                msg,
+                &account_id,
+                NO_DEPOSIT,
+                env::prepaid_gas() - GAS_FOR_NFT_APPROVE,
+            )
+        })
+    }
+
+    #[payable]
+    fn nft_revoke(&mut self, token_id: TokenId, account_id: ValidAccountId) {
+        assert_one_yocto();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(&env::predecessor_account_id(), &owner_id, "Predecessor must be token owner.");
+        if account_id.as_ref() == &env::current_account_id() {
+            self.assert_no_bids(&token_id);
+        }
+        self.internal_revoke(&token_id, &owner_id, account_id.as_ref());
+    }
+
+    #[payable]
+    fn nft_revoke_all(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(&env::predecessor_account_id(), &owner_id, "Predecessor must be token owner.");
+        self.assert_no_bids(&token_id);
+
+        if let Some(approved_account_ids) =
+            self.tokens.approvals_by_id.as_mut().unwrap().remove(&token_id)
+        {
+            let storage_released =
+                approved_account_ids.keys().map(bytes_for_approved_account_id).sum();
+            self.internal_refund_storage(&owner_id, storage_released);
+        }
+    }
+
+    fn nft_is_approved(
+        self,
+        token_id: TokenId,
+        approved_account_id: ValidAccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.tokens.nft_is_approved(token_id, approved_account_id, approval_id)
+    }
+}
+
+impl Contract {
+    /// Approves `account_id` on `token_id`, returning the approval id and the storage taken.
+    /// Replacing an existing approval of the same account takes no extra storage.
+    pub(crate) fn internal_approve(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+    ) -> (u64, StorageUsage) {
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let next_approval_id_by_id = self.tokens.next_approval_id_by_id.as_mut().unwrap();
+        let mut approved_account_ids = approvals_by_id.get(token_id).unwrap_or_default();
+        let approval_id = next_approval_id_by_id.get(token_id).unwrap_or(1);
+        let old_approval_id = approved_account_ids.insert(account_id.clone(), approval_id);
+        approvals_by_id.insert(token_id, &approved_account_ids);
+        next_approval_id_by_id.insert(token_id, &(approval_id + 1));
+
+        let storage_used =
+            if old_approval_id.is_none() { bytes_for_approved_account_id(account_id) } else { 0 };
+        (approval_id, storage_used)
+    }
+
+    /// Returns the storage of `approved_account_ids`, cleared by a transfer, to `owner_id`.
+    /// Returns the bytes refunded, so that the transfer doesn't credit them a second time.
+    pub(crate) fn internal_refund_approvals(
+        &mut self,
+        owner_id: &AccountId,
+        approved_account_ids: &HashMap<AccountId, u64>,
+    ) -> StorageUsage {
+        let storage_released = approvals_storage(approved_account_ids);
+        self.internal_refund_storage(owner_id, storage_released);
+        storage_released
+    }
+
+    /// Revokes the approval of `account_id` on `token_id`, if any, refunding its storage to
+    /// `owner_id`.
+    pub(crate) fn internal_revoke(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        account_id: &AccountId,
+    ) {
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        if let Some(mut approved_account_ids) = approvals_by_id.get(token_id) {
+            if approved_account_ids.remove(account_id).is_some() {
+                if approved_account_ids.is_empty() {
+                    approvals_by_id.remove(token_id);
+                } else {
+                    approvals_by_id.insert(token_id, &approved_account_ids);
+                }
+                self.internal_refund_storage(owner_id, bytes_for_approved_account_id(account_id));
+            }
+        }
+    }
+}
+
+/// Bytes taken by the approvals of a token.
+pub(crate) fn approvals_storage(approved_account_ids: &HashMap<AccountId, u64>) -> StorageUsage {
+    approved_account_ids.keys().map(bytes_for_approved_account_id).sum()
+}
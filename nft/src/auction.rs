@@ -0,0 +1,242 @@
+use crate::events::log_whitelist_event;
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+/// Highest bid of an auction. The bid amount is held by the contract until the auction ends.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bid {
+    pub bidder_id: AccountId,
+    pub amount: U128,
+    pub placed_at: U64,
+}
+
+/// English auction of a token. The seller keeps the token until settlement, the contract
+/// being approved on it to hand it over to the highest bidder. Once the token is bid on, the
+/// seller can neither move it, burn it nor revoke the contract's approval.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    pub token_id: TokenId,
+    pub seller_id: AccountId,
+    /// Lowest bid accepted, in yoctoNEAR.
+    pub reserve_price: U128,
+    /// When bidding closes, in nanoseconds.
+    pub ends_at: U64,
+    /// Id of the approval given to the contract on the token.
+    pub approval_id: u64,
+    pub highest_bid: Option<Bid>,
+    /// Storage paid by the seller for the auction, refunded once it's over.
+    #[serde(skip)]
+    storage_used: StorageUsage,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Puts `token_id` up for auction until `ends_at`, approving the contract on it. Only the
+    /// token owner can start an auction, and pays for its storage.
+    #[payable]
+    pub fn start_auction(
+        &mut self,
+        token_id: TokenId,
+        reserve_price: U128,
+        ends_at: U64,
+    ) -> Auction {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), owner_id, "Only the token owner can auction it");
+        assert!(self.auctions.get(&token_id).is_none(), "Token is already on auction");
+        assert!(self.listings.get(&token_id).is_none(), "Token is listed, delist it first");
+        assert!(ends_at.0 > env::block_timestamp(), "Auction must end in the future");
+        self.assert_transferable(&token_id);
+
+        let (approval_id, approval_storage) =
+            self.internal_approve(&token_id, &env::current_account_id());
+        let initial_storage_usage = env::storage_usage();
+        let mut auction = Auction {
+            token_id: token_id.clone(),
+            seller_id: owner_id.clone(),
+            reserve_price,
+            ends_at,
+            approval_id,
+            highest_bid: None,
+            storage_used: 0,
+        };
+        self.auctions.insert(&token_id, &auction);
+        auction.storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        self.auctions.insert(&token_id, &auction);
+        self.internal_charge_storage(&owner_id, approval_storage + auction.storage_used);
+
+        log_whitelist_event(
+            "auction_start",
+            json!([{
+                "token_id": token_id,
+                "seller_id": owner_id,
+                "reserve_price": reserve_price,
+                "ends_at": ends_at,
+            }]),
+        );
+        auction
+    }
+
+    /// Bids the attached deposit on `token_id`. The bid has to reach the reserve price and top
+    /// the highest bid, which is refunded to its bidder.
+    #[payable]
+    pub fn bid(&mut self, token_id: TokenId) {
+        let mut auction = self.auctions.get(&token_id).expect("Token is not on auction");
+        assert!(env::block_timestamp() < auction.ends_at.0, "Auction has ended");
+        let bidder_id = env::predecessor_account_id();
+        assert_ne!(bidder_id, auction.seller_id, "The seller can't bid on their own token");
+        let amount = env::attached_deposit();
+        assert!(amount >= auction.reserve_price.0, "Bid is below the reserve price");
+        if let Some(highest_bid) = &auction.highest_bid {
+            assert!(
+                amount > highest_bid.amount.0,
+                "Bid must be higher than the current bid of {}",
+                highest_bid.amount.0
+            );
+            Promise::new(highest_bid.bidder_id.clone()).transfer(highest_bid.amount.0);
+        }
+
+        auction.highest_bid = Some(Bid {
+            bidder_id: bidder_id.clone(),
+            amount: amount.into(),
+            placed_at: env::block_timestamp().into(),
+        });
+        self.auctions.insert(&token_id, &auction);
+        log_whitelist_event(
+            "auction_bid",
+            json!([{ "token_id": token_id, "bidder_id": bidder_id, "amount": U128(amount) }]),
+        );
+    }
+
+    /// Ends the auction of `token_id` once bidding has closed. Callable by anyone. The token
+    /// goes to the highest bidder and the bid is paid out to the royalty receivers of the token
+    /// and the seller. The bid is refunded instead if the contract is no longer approved on the
+    /// token, or if the token can't be transferred anymore, e.g. once it became soulbound or
+    /// expired. Returns true if the token was sold.
+    pub fn settle_auction(&mut self, token_id: TokenId) -> bool {
+        let auction = self.auctions.get(&token_id).expect("Token is not on auction");
+        assert!(env::block_timestamp() >= auction.ends_at.0, "Auction is still running");
+        self.internal_delete_auction(&auction);
+
+        let contract_id = env::current_account_id();
+        let approval_id = self
+            .tokens
+            .approvals_by_id
+            .as_ref()
+            .and_then(|by_id| by_id.get(&token_id))
+            .and_then(|approved_account_ids| approved_account_ids.get(&contract_id).copied());
+        let transferable = self.internal_is_transferable(&token_id);
+        let highest_bid = match auction.highest_bid.clone() {
+            Some(highest_bid) if approval_id.is_some() && transferable => highest_bid,
+            highest_bid => {
+                // nobody bid, the approval is gone or the token can't move
+                if let Some(highest_bid) = highest_bid {
+                    Promise::new(highest_bid.bidder_id).transfer(highest_bid.amount.0);
+                }
+                self.internal_revoke(&token_id, &auction.seller_id, &contract_id);
+                log_whitelist_event(
+                    "auction_settle",
+                    json!([{
+                        "token_id": token_id,
+                        "seller_id": auction.seller_id,
+                        "sold": false,
+                    }]),
+                );
+                return false;
+            }
+        };
+
+        let (_, approved_account_ids) = self.tokens.internal_transfer(
+            &contract_id,
+            &highest_bid.bidder_id,
+            &token_id,
+            approval_id,
+            None,
+        );
+        if let Some(approved_account_ids) = approved_account_ids {
+            self.internal_refund_approvals(&auction.seller_id, &approved_account_ids);
+        }
+        self.internal_on_transfer(&token_id, &auction.seller_id, &highest_bid.bidder_id);
+
+        self.internal_pay_sale(&token_id, &auction.seller_id, highest_bid.amount.into());
+        log_whitelist_event(
+            "auction_settle",
+            json!([{
+                "token_id": token_id,
+                "seller_id": auction.seller_id,
+                "buyer_id": highest_bid.bidder_id,
+                "price": highest_bid.amount,
+                "sold": true,
+            }]),
+        );
+        true
+    }
+
+    /// Calls off the auction of `token_id` and revokes the contract's approval. Only the seller
+    /// can cancel, as long as nobody has bid.
+    #[payable]
+    pub fn cancel_auction(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let auction = self.auctions.get(&token_id).expect("Token is not on auction");
+        assert_eq!(env::predecessor_account_id(), auction.seller_id, "Only the seller can cancel");
+        assert!(auction.highest_bid.is_none(), "Can't cancel an auction with bids");
+        self.internal_delete_auction(&auction);
+        self.internal_revoke(&token_id, &auction.seller_id, &env::current_account_id());
+        log_whitelist_event(
+            "auction_cancel",
+            json!([{ "token_id": token_id, "seller_id": auction.seller_id }]),
+        );
+    }
+
+    pub fn get_auction(&self, token_id: TokenId) -> Option<Auction> {
+        self.auctions.get(&token_id)
+    }
+
+    pub fn get_auction_count(&self) -> u64 {
+        self.auctions.len()
+    }
+
+    /// Lists the running and unsettled auctions by offset.
+    pub fn get_auctions(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Auction> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let auctions = self.auctions.values_as_vector();
+        (start_index as u64..auctions.len()).take(limit).filter_map(|i| auctions.get(i)).collect()
+    }
+}
+
+impl Contract {
+    /// Panics if `token_id` is on auction and has been bid on. The seller can't move, burn or
+    /// take the token back from the contract until the auction is settled.
+    pub(crate) fn assert_no_bids(&self, token_id: &TokenId) {
+        if self.auctions.get(token_id).map_or(false, |auction| auction.highest_bid.is_some()) {
+            env::panic(b"Token is on auction with bids");
+        }
+    }
+
+    /// Removes `auction` and refunds its storage to the seller.
+    fn internal_delete_auction(&mut self, auction: &Auction) {
+        self.auctions.remove(&auction.token_id);
+        self.internal_refund_storage(&auction.seller_id, auction.storage_used);
+    }
+
+    /// Calls off the auction of `token_id`, if any, refunding the highest bid. Called whenever
+    /// the token moves or is burned outside of the auction.
+    pub(crate) fn internal_remove_auction(&mut self, token_id: &TokenId) {
+        let auction = match self.auctions.get(token_id) {
+            Some(auction) => auction,
+            None => return,
+        };
+        self.internal_delete_auction(&auction);
+        if let Some(highest_bid) = &auction.highest_bid {
+            Promise::new(highest_bid.bidder_id.clone()).transfer(highest_bid.amount.0);
+        }
+        log_whitelist_event(
+            "auction_cancel",
+            json!([{ "token_id": token_id, "seller_id": auction.seller_id }]),
+        );
+    }
+}
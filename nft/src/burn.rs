@@ -0,0 +1,92 @@
+use crate::approval::approvals_storage;
+use crate::events::log_nft_event;
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+#[near_bindgen]
+impl Contract {
+    /// Destroys `token_id`. Callable by the token owner or by an account approved on the token,
+    /// unless the token is on auction with bids. The storage of the token's approvals is credited
+    /// back to its owner, the rest was paid by the contract at mint and stays with it.
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        let sender_id = env::predecessor_account_id();
+        if sender_id != owner_id {
+            let approved = self
+                .tokens
+                .approvals_by_id
+                .as_ref()
+                .and_then(|by_id| by_id.get(&token_id))
+                .map_or(false, |approved_account_ids| {
+                    approved_account_ids.contains_key(&sender_id)
+                });
+            assert!(approved, "Only the token owner or an approved account can burn the token");
+        }
+        self.assert_no_bids(&token_id);
+
+        self.internal_burn(&token_id, &owner_id, &owner_id);
+
+        let mut data = json!({ "owner_id": owner_id, "token_ids": [token_id] });
+        if sender_id != owner_id {
+            data["authorized_id"] = json!(sender_id);
+        }
+        log_nft_event("nft_burn", json!([data]));
+    }
+
+    /// Number of tokens minted and not burned yet.
+    pub fn nft_circulating_supply(&self) -> u64 {
+        self.circulating_supply
+    }
+}
+
+impl Contract {
+    /// Removes `token_id`, currently held by `owner_id`, from every collection it is stored in
+    /// and refunds the storage of its approvals, the only part paid by its holders, to
+    /// `refund_id`.
+    pub(crate) fn internal_burn(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        refund_id: &AccountId,
+    ) {
+        // they refund their own storage to the seller
+        self.internal_remove_listing(token_id);
+        self.internal_remove_auction(token_id);
+
+        self.tokens.owner_by_id.remove(token_id);
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            let mut owner_tokens =
+                tokens_per_owner.get(owner_id).expect("Unable to access tokens per owner");
+            owner_tokens.remove(token_id);
+            if owner_tokens.is_empty() {
+                tokens_per_owner.remove(owner_id);
+            } else {
+                tokens_per_owner.insert(owner_id, &owner_tokens);
+            }
+        }
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            token_metadata_by_id.remove(token_id);
+        }
+        let approvals_released = self
+            .tokens
+            .approvals_by_id
+            .as_mut()
+            .and_then(|by_id| by_id.remove(token_id))
+            .map_or(0, |approved_account_ids| approvals_storage(&approved_account_ids));
+        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
+            next_approval_id_by_id.remove(token_id);
+        }
+        self.token_royalties.remove(token_id);
+        self.locked_token_metadata.remove(token_id);
+        self.token_held_since.remove(token_id);
+        self.soulbound_tokens.remove(token_id);
+        self.transfer_unlocks_at.remove(token_id);
+        self.internal_unindex_token(token_id, owner_id);
+        self.internal_sync_holder(owner_id);
+        self.circulating_supply -= 1;
+
+        self.internal_refund_storage(refund_id, approvals_released);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+
+/// Template giving tokens their bare sequence number as id, e.g. "37".
+pub const DEFAULT_TOKEN_ID_TEMPLATE: &str = "{sequence}";
+const TOKEN_ID_PLACEHOLDERS: [&str; 3] = ["{sequence}", "{variant}", "{edition}"];
+/// Variant letters given out by `nft_mint_owner`.
+pub const VARIANTS: [&str; 5] = ["a", "b", "c", "d", "e"];
+
+/// Marketplace trait, written to `extra` as `{"attributes":[...]}` when a token is minted.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenAttribute {
+    pub trait_type: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<String>,
+}
+
+/// Metadata shared by every token of a variant.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VariantConfig {
+    /// Image of the variant, usually a CID path relative to the contract's `base_uri`.
+    /// Defaults to the images the collection launched with.
+    #[serde(default)]
+    pub media: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<TokenAttribute>,
+    /// URL of an off-chain JSON file with more info, copied to each token's `reference`.
+    pub reference: Option<String>,
+    /// Base64-encoded sha256 hash of the `reference` JSON, required along with it.
+    pub reference_hash: Option<Base64VecU8>,
+    /// Whether tokens minted for the variant are bound to their first owner.
+    #[serde(default)]
+    pub soulbound: bool,
+}
+
+/// Settings of the collection that can be changed by the owner after deployment.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionConfig {
+    /// Id given to minted tokens. `{sequence}` is replaced with the token number, `{variant}`
+    /// with the variant letter and `{edition}` with the token's number within its variant.
+    pub token_id_template: String,
+    /// Traits and reference of the tokens minted for each variant letter.
+    #[serde(default)]
+    pub variants: HashMap<String, VariantConfig>,
+    /// Whether token media is rendered on chain as an SVG, see `set_on_chain_media`.
+    #[serde(default)]
+    pub on_chain_media: bool,
+    /// Tiers tokens evolve through while held, see `set_evolution_tiers`.
+    #[serde(default)]
+    pub evolution_tiers: Vec<EvolutionTier>,
+    /// Validity window copied to the tokens minted, see `set_validity_window`.
+    #[serde(default)]
+    pub starts_at: Option<U64>,
+    #[serde(default)]
+    pub expires_at: Option<U64>,
+    /// Whether tokens outside their validity window are locked in place.
+    #[serde(default)]
+    pub enforce_validity_on_transfer: bool,
+    /// Nanoseconds after mint during which tokens can't be transferred.
+    #[serde(default)]
+    pub transfer_lock_ns: Option<U64>,
+}
+
+impl Default for CollectionConfig {
+    fn default() -> Self {
+        Self {
+            token_id_template: DEFAULT_TOKEN_ID_TEMPLATE.to_string(),
+            variants: HashMap::new(),
+            on_chain_media: false,
+            evolution_tiers: vec![],
+            starts_at: None,
+            expires_at: None,
+            enforce_validity_on_transfer: false,
+            transfer_lock_ns: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_collection_config(&self) -> CollectionConfig {
+        self.internal_config()
+    }
+
+    /// Sets the id template of minted tokens, e.g. `hrms1-{variant}-{edition}`. The template
+    /// is fixed by the first mint, so that ids generated by two templates can't clash.
+    pub fn set_token_id_template(&mut self, token_id_template: String) {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        assert_eq!(self.token_minted, 0, "Token id template can't change once tokens are minted");
+        assert_valid_token_id_template(&token_id_template);
+        let mut config = self.internal_config();
+        config.token_id_template = token_id_template;
+        self.config.set(&config);
+    }
+
+    /// Sets the traits and reference given to tokens of `variant` minted from now on.
+    pub fn set_variant_config(&mut self, variant: String, variant_config: VariantConfig) {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        assert!(VARIANTS.contains(&variant.as_str()), "Unknown variant");
+        assert_eq!(
+            variant_config.reference.is_some(),
+            variant_config.reference_hash.is_some(),
+            "Reference and reference hash must be set together"
+        );
+        if let Some(reference_hash) = &variant_config.reference_hash {
+            assert_eq!(reference_hash.0.len(), 32, "Reference hash has to be 32 bytes");
+        }
+        let mut config = self.internal_config();
+        config.variants.insert(variant, variant_config);
+        self.config.set(&config);
+    }
+
+    /// Token with the sequence number `number`, whatever its id.
+    pub fn nft_token_by_number(&self, number: U64) -> Option<Token> {
+        self.tokens_by_sequence.get(&number.into()).map(|token_id| self.internal_token(&token_id))
+    }
+
+    /// Sequence number of `token_id`.
+    pub fn nft_token_number(&self, token_id: TokenId) -> Option<U64> {
+        self.token_sequence.get(&token_id).map(U64)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_config(&self) -> CollectionConfig {
+        self.config.get().unwrap_or_default()
+    }
+
+    pub(crate) fn internal_variant_config(&self, variant: &str) -> VariantConfig {
+        self.internal_config().variants.remove(variant).unwrap_or_default()
+    }
+
+    /// Renders the id template for a minted token.
+    pub(crate) fn internal_token_id(&self, sequence: u64, variant: &str, edition: u64) -> TokenId {
+        self.internal_config()
+            .token_id_template
+            .replace("{sequence}", &sequence.to_string())
+            .replace("{variant}", variant)
+            .replace("{edition}", &edition.to_string())
+    }
+
+    /// Panics if `token_id`, passed explicitly to `mint`, could clash with a generated id.
+    pub(crate) fn assert_not_generated_token_id(&self, token_id: &str) {
+        assert!(
+            token_id.parse::<u64>().is_err(),
+            "Numeric token ids are reserved for the token sequence"
+        );
+        let template = self.internal_config().token_id_template;
+        let prefix = &template[..template.find('{').unwrap_or(template.len())];
+        assert!(
+            prefix.is_empty() || !token_id.starts_with(prefix),
+            "Token ids starting with {} are reserved for minted tokens",
+            prefix
+        );
+    }
+}
+
+fn assert_valid_token_id_template(template: &str) {
+    let unique = template.contains("{sequence}")
+        || (template.contains("{variant}") && template.contains("{edition}"));
+    assert!(unique, "Token id template must contain the sequence, or the variant and the edition");
+    // the prefix is reserved from explicit mints, bare sequence numbers are reserved as numeric
+    assert!(
+        template == DEFAULT_TOKEN_ID_TEMPLATE || !template.starts_with('{'),
+        "Token id template must start with a literal prefix"
+    );
+
+    let literal = TOKEN_ID_PLACEHOLDERS
+        .iter()
+        .fold(template.to_string(), |rest, placeholder| rest.replace(placeholder, ""));
+    assert!(
+        literal.chars().all(|c| c.is_ascii_alphanumeric() || "-_.#".contains(c)),
+        "Token id template can only contain placeholders, letters, digits and -_.#"
+    );
+}
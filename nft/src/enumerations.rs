@@ -1,77 +1,283 @@
+use crate::internal::set_extra_field;
 use crate::*;
+use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+use near_sdk::serde_json::Value;
 
+const DEFAULT_PAGE_LIMIT: u64 = 50;
+const MAX_PAGE_LIMIT: u64 = 100;
+
+/// One page of a cursor-paginated listing.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenPage {
+    pub tokens: Vec<Token>,
+    /// Cursor to pass to get the next page, `None` once the listing is exhausted.
+    pub next_cursor: Option<U64>,
+}
+
+/// NEP-181 enumeration. Tokens are listed in mint order instead of the lexicographic order of
+/// their ids, so "10" comes after "9".
 #[near_bindgen]
-impl Contract {
+impl NonFungibleTokenEnumeration for Contract {
+    fn nft_total_supply(self) -> U128 {
+        // counts legacy tokens still waiting for `backfill_legacy_tokens` too
+        U128(self.circulating_supply as u128)
+    }
 
-    //Query for the total supply of NFTs on the contract
-    pub fn nft_total_supply(&self) -> U128 {
-        //return the length of the token metadata by ID
-        U128(self.token_metadata_by_id.len() as u128)
-    }
-
-    //Query for nft tokens on the contract regardless of the owner using pagination
-    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
-        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
-        let start = u128::from(from_index.unwrap_or(U128(0)));
-
-        //iterate through each token using an iterator
-        self.token_metadata_by_id.keys()
-            //skip to the index we specified in the start variable
-            .skip(start as usize) 
-            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
-            .take(limit.unwrap_or(50) as usize) 
-            //we'll map the token IDs which are strings into Json Tokens
-            .map(|token_id| self.nft_token(token_id.clone()).unwrap())
-            //since we turned the keys into an iterator, we need to turn it back into a vector to return
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            (self.tokens_by_sequence.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        self.tokens_by_sequence
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(_, token_id)| self.internal_token(&token_id))
             .collect()
     }
 
-    //get the total supply of NFTs for a given owner
-    pub fn nft_supply_for_owner(
+    fn nft_supply_for_owner(self, account_id: ValidAccountId) -> U128 {
+        self.tokens
+            .tokens_per_owner
+            .as_ref()
+            .and_then(|tokens_per_owner| tokens_per_owner.get(account_id.as_ref()))
+            .map(|owner_tokens| U128(owner_tokens.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    fn nft_tokens_for_owner(
         &self,
-        account_id: AccountId,
-    ) -> U128 {
-        //get the set of tokens for the passed in owner
-        let tokens_for_owner_set = self.tokens_per_owner.get(&account_id);
-
-        //if there is some set of tokens, we'll return the length as a U128
-        if let Some(tokens_for_owner_set) = tokens_for_owner_set {
-            U128(tokens_for_owner_set.len() as u128)
-        } else {
-            //if there isn't a set of tokens for the passed in account ID, we'll return 0
-            U128(0)
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let owner_tokens = match self
+            .tokens
+            .tokens_per_owner
+            .as_ref()
+            .and_then(|tokens_per_owner| tokens_per_owner.get(account_id.as_ref()))
+        {
+            Some(owner_tokens) => owner_tokens,
+            None => return vec![],
+        };
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            owner_tokens.len() as u128 > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        owner_tokens
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|token_id| self.internal_token(&token_id))
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Lists tokens in mint order, starting after `cursor`.
+    ///
+    /// Cursors stay valid when tokens are burned or transferred, unlike `from_index` offsets.
+    pub fn nft_tokens_page(&self, cursor: Option<U64>, limit: Option<u64>) -> TokenPage {
+        self.internal_page(&self.tokens_by_sequence, cursor, limit)
+    }
+
+    /// Lists the tokens of `variant` in mint order, starting after `cursor`.
+    pub fn nft_tokens_for_variant_page(
+        &self,
+        variant: String,
+        cursor: Option<U64>,
+        limit: Option<u64>,
+    ) -> TokenPage {
+        match self.variant_tokens.get(&variant) {
+            Some(variant_tokens) => self.internal_page(&variant_tokens, cursor, limit),
+            None => TokenPage { tokens: vec![], next_cursor: None },
         }
     }
 
-    //Query for all the tokens for an owner
-    pub fn nft_tokens_for_owner(
+    /// Number of live tokens of `variant`.
+    pub fn nft_supply_by_variant(&self, variant: String) -> U128 {
+        self.variant_tokens
+            .get(&variant)
+            .map(|variant_tokens| U128(variant_tokens.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    /// Lists the tokens of `variant` in mint order by offset, like `nft_tokens_for_owner`.
+    /// Offsets shift when tokens are burned, use `nft_tokens_for_variant_page` to walk a
+    /// variant consistently.
+    pub fn nft_tokens_by_variant(
         &self,
-        account_id: AccountId,
+        variant: String,
         from_index: Option<U128>,
         limit: Option<u64>,
-    ) -> Vec<JsonToken> {
-        //get the set of tokens for the passed in owner
-        let tokens_for_owner_set = self.tokens_per_owner.get(&account_id);
-        //if there is some set of tokens, we'll set the tokens variable equal to that set
-        let tokens = if let Some(tokens_for_owner_set) = tokens_for_owner_set {
-            tokens_for_owner_set
-        } else {
-            //if there is no set of tokens, we'll simply return an empty vector. 
-            return vec![];
+    ) -> Vec<Token> {
+        let variant_tokens = match self.variant_tokens.get(&variant) {
+            Some(variant_tokens) if variant_tokens.len() > 0 => variant_tokens,
+            _ => return vec![],
         };
-
-        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
-        let start = u128::from(from_index.unwrap_or(U128(0)));
-
-        //iterate through the keys vector
-        tokens.iter()
-            //skip to the index we specified in the start variable
-            .skip(start as usize) 
-            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
-            .take(limit.unwrap_or(50) as usize) 
-            //we'll map the token IDs which are strings into Json Tokens
-            .map(|token_id| self.nft_token(token_id.clone()).unwrap())
-            //since we turned the keys into an iterator, we need to turn it back into a vector to return
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            variant_tokens.len() as u128 > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        variant_tokens
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(_, token_id)| self.internal_token(&token_id))
             .collect()
     }
-}
\ No newline at end of file
+
+    /// Lists the tokens held by `account_id` in mint order, starting after `cursor`. Legacy
+    /// tokens are only listed once `backfill_legacy_tokens` has indexed them.
+    pub fn nft_tokens_for_owner_page(
+        &self,
+        account_id: ValidAccountId,
+        cursor: Option<U64>,
+        limit: Option<u64>,
+    ) -> TokenPage {
+        match self.owner_tokens.get(account_id.as_ref()) {
+            Some(owner_tokens) => self.internal_page(&owner_tokens, cursor, limit),
+            None => TokenPage { tokens: vec![], next_cursor: None },
+        }
+    }
+}
+
+fn page_limit(limit: Option<u64>) -> usize {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    assert_ne!(limit, 0, "Cannot provide limit of 0.");
+    limit.min(MAX_PAGE_LIMIT) as usize
+}
+
+impl Contract {
+    /// Builds the `Token` view of a live token, evolved to its tier, flagged as `redeemed` once
+    /// its whitelist right is used and with its media rendered on chain if enabled.
+    pub(crate) fn internal_token(&self, token_id: &TokenId) -> Token {
+        let owner_id = self.tokens.owner_by_id.get(token_id).expect("Token not found");
+        let mut metadata =
+            self.tokens.token_metadata_by_id.as_ref().and_then(|by_id| by_id.get(token_id));
+        if let Some(metadata) = metadata.as_mut() {
+            self.internal_evolve(token_id, metadata);
+            if self.redemptions.contains_key(token_id) {
+                set_extra_field(metadata, "redeemed", Value::Bool(true));
+            }
+            self.internal_render_media(token_id, metadata);
+        }
+        let approved_account_ids = self
+            .tokens
+            .approvals_by_id
+            .as_ref()
+            .map(|by_id| by_id.get(token_id).unwrap_or_default());
+        Token { token_id: token_id.clone(), owner_id, metadata, approved_account_ids }
+    }
+
+    /// Adds a freshly minted token to the enumeration indexes.
+    pub(crate) fn internal_index_token(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        sequence: u64,
+        variant: Option<&str>,
+    ) {
+        self.tokens_by_sequence.insert(&sequence, token_id);
+        self.token_sequence.insert(token_id, &sequence);
+        self.internal_index_owner_token(owner_id, sequence, token_id);
+        let variant = match variant {
+            Some(variant) => variant.to_string(),
+            None => return,
+        };
+        self.token_variants.insert(token_id, &variant);
+
+        let mut variant_tokens = self.variant_tokens.get(&variant).unwrap_or_else(|| {
+            TreeMap::new(StorageKey::VariantTokensInner { variant: variant.clone() })
+        });
+        variant_tokens.insert(&sequence, token_id);
+        self.variant_tokens.insert(&variant, &variant_tokens);
+    }
+
+    /// Removes a burned token from the enumeration indexes.
+    pub(crate) fn internal_unindex_token(&mut self, token_id: &TokenId, owner_id: &AccountId) {
+        let sequence = match self.token_sequence.remove(token_id) {
+            Some(sequence) => sequence,
+            None => return,
+        };
+        self.tokens_by_sequence.remove(&sequence);
+        self.internal_unindex_owner_token(owner_id, sequence);
+        if let Some(variant) = self.token_variants.remove(token_id) {
+            if let Some(mut variant_tokens) = self.variant_tokens.get(&variant) {
+                variant_tokens.remove(&sequence);
+                self.variant_tokens.insert(&variant, &variant_tokens);
+            }
+        }
+    }
+
+    /// Moves `token_id` to the index of its new owner. Legacy tokens that aren't indexed yet
+    /// are left to `backfill_legacy_tokens`.
+    pub(crate) fn internal_reindex_owner(
+        &mut self,
+        token_id: &TokenId,
+        old_owner_id: &AccountId,
+        new_owner_id: &AccountId,
+    ) {
+        if let Some(sequence) = self.token_sequence.get(token_id) {
+            self.internal_unindex_owner_token(old_owner_id, sequence);
+            self.internal_index_owner_token(new_owner_id, sequence, token_id);
+        }
+    }
+
+    fn internal_index_owner_token(
+        &mut self,
+        owner_id: &AccountId,
+        sequence: u64,
+        token_id: &TokenId,
+    ) {
+        let mut owner_tokens = self.owner_tokens.get(owner_id).unwrap_or_else(|| {
+            TreeMap::new(StorageKey::OwnerTokensInner { account_id: owner_id.clone() })
+        });
+        owner_tokens.insert(&sequence, token_id);
+        self.owner_tokens.insert(owner_id, &owner_tokens);
+    }
+
+    fn internal_unindex_owner_token(&mut self, owner_id: &AccountId, sequence: u64) {
+        if let Some(mut owner_tokens) = self.owner_tokens.get(owner_id) {
+            owner_tokens.remove(&sequence);
+            if owner_tokens.len() == 0 {
+                self.owner_tokens.remove(owner_id);
+            } else {
+                self.owner_tokens.insert(owner_id, &owner_tokens);
+            }
+        }
+    }
+
+    fn internal_page(
+        &self,
+        index: &TreeMap<u64, TokenId>,
+        cursor: Option<U64>,
+        limit: Option<u64>,
+    ) -> TokenPage {
+        let limit = page_limit(limit);
+        let mut entries: Vec<(u64, TokenId)> = match cursor {
+            Some(cursor) => index.iter_from(cursor.into()).take(limit + 1).collect(),
+            None => index.iter().take(limit + 1).collect(),
+        };
+
+        let has_more = entries.len() > limit;
+        entries.truncate(limit);
+        let next_cursor =
+            if has_more { entries.last().map(|(sequence, _)| U64(*sequence)) } else { None };
+        TokenPage {
+            tokens: entries.iter().map(|(_, token_id)| self.internal_token(token_id)).collect(),
+            next_cursor,
+        }
+    }
+}
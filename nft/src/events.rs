@@ -0,0 +1,43 @@
+use crate::*;
+use near_sdk::serde_json::Value;
+
+/// Standard and version of the NEP-171 events logged by this contract.
+pub const NFT_STANDARD_NAME: &str = "nep171";
+pub const NFT_STANDARD_VERSION: &str = "1.0.0";
+/// Version of NEP-171 that introduced `contract_metadata_update`.
+pub const NFT_METADATA_UPDATE_VERSION: &str = "1.1.0";
+/// Standard and version of the events specific to the NEARHUB whitelist passes.
+pub const WHITELIST_STANDARD_NAME: &str = "nearhub_whitelist";
+pub const WHITELIST_STANDARD_VERSION: &str = "1.0.0";
+
+/// Logs a NEP-297 event, `EVENT_JSON:` followed by the serialized event.
+pub(crate) fn log_event(standard: &str, version: &str, event: &str, data: Value) {
+    let event = json!({
+        "standard": standard,
+        "version": version,
+        "event": event,
+        "data": data,
+    });
+    env::log(format!("EVENT_JSON:{}", event).as_bytes());
+}
+
+/// Logs one of the events defined by NEP-171, e.g. `nft_burn`.
+pub(crate) fn log_nft_event(event: &str, data: Value) {
+    log_event(NFT_STANDARD_NAME, NFT_STANDARD_VERSION, event, data);
+}
+
+/// Logs the NEP-171 `contract_metadata_update` event after `fields` of the contract metadata
+/// changed, with the new metadata so indexers don't need to query it.
+pub(crate) fn log_contract_metadata_update(metadata: &NFTContractMetadata, fields: &str) {
+    log_event(
+        NFT_STANDARD_NAME,
+        NFT_METADATA_UPDATE_VERSION,
+        "contract_metadata_update",
+        json!([{ "memo": format!("Updated {}", fields), "metadata": metadata }]),
+    );
+}
+
+/// Logs an event specific to the whitelist collection, e.g. `whitelist_redeem`.
+pub(crate) fn log_whitelist_event(event: &str, data: Value) {
+    log_event(WHITELIST_STANDARD_NAME, WHITELIST_STANDARD_VERSION, event, data);
+}
@@ -0,0 +1,99 @@
+use crate::internal::set_extra_field;
+use crate::*;
+use near_sdk::serde_json::Value;
+
+const MAX_EVOLUTION_TIERS: usize = 10;
+
+/// Stage a token reaches once its owner has held it long enough.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EvolutionTier {
+    /// Nanoseconds the current owner has to hold the token for it to reach this tier.
+    pub min_holding_ns: U64,
+    /// Appended to the token's title and written to `extra` as `tier`.
+    pub name: String,
+    /// Image shown instead of the token's own, resolved like the variant media.
+    #[serde(default)]
+    pub media: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the tiers tokens evolve through while held, ordered by increasing holding time.
+    /// An empty list turns evolution off.
+    pub fn set_evolution_tiers(&mut self, tiers: Vec<EvolutionTier>) {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        assert!(tiers.len() <= MAX_EVOLUTION_TIERS, "Too many evolution tiers");
+        assert!(tiers.iter().all(|tier| !tier.name.is_empty()), "Tier name can't be empty");
+        assert!(
+            tiers.windows(2).all(|pair| pair[0].min_holding_ns.0 < pair[1].min_holding_ns.0),
+            "Tiers must be ordered by increasing holding time"
+        );
+        let mut config = self.internal_config();
+        config.evolution_tiers = tiers;
+        self.config.set(&config);
+    }
+
+    /// When the current owner of `token_id` received it, in nanoseconds.
+    pub fn token_held_since(&self, token_id: TokenId) -> Option<U64> {
+        self.internal_held_since(&token_id).map(U64)
+    }
+
+    /// Name of the tier `token_id` has reached, if any.
+    pub fn nft_token_tier(&self, token_id: TokenId) -> Option<String> {
+        self.internal_token_tier(&token_id).map(|tier| tier.name)
+    }
+}
+
+impl Contract {
+    /// Tokens minted before holding times were tracked count from their `issued_at`.
+    pub(crate) fn internal_held_since(&self, token_id: &TokenId) -> Option<u64> {
+        self.token_held_since.get(token_id).or_else(|| {
+            self.tokens
+                .token_metadata_by_id
+                .as_ref()
+                .and_then(|by_id| by_id.get(token_id))
+                .and_then(|metadata| metadata.issued_at)
+                .and_then(|issued_at| issued_at.parse().ok())
+        })
+    }
+
+    /// Puts back the holding time of a token whose transfer was reverted.
+    pub(crate) fn internal_restore_held_since(
+        &mut self,
+        token_id: &TokenId,
+        held_since: Option<u64>,
+    ) {
+        match held_since {
+            Some(held_since) => self.token_held_since.insert(token_id, &held_since),
+            None => self.token_held_since.remove(token_id),
+        };
+    }
+
+    pub(crate) fn internal_token_tier(&self, token_id: &TokenId) -> Option<EvolutionTier> {
+        let tiers = self.internal_config().evolution_tiers;
+        if tiers.is_empty() {
+            return None;
+        }
+        let held_for = env::block_timestamp().saturating_sub(self.internal_held_since(token_id)?);
+        tiers.into_iter().rev().find(|tier| held_for >= tier.min_holding_ns.0)
+    }
+
+    /// Shows the tier `token_id` has reached in its title, media and `extra`.
+    pub(crate) fn internal_evolve(&self, token_id: &TokenId, metadata: &mut TokenMetadata) {
+        let tier = match self.internal_token_tier(token_id) {
+            Some(tier) => tier,
+            None => return,
+        };
+        if let Some(title) = metadata.title.as_mut() {
+            title.push_str(" · ");
+            title.push_str(&tier.name);
+        }
+        if let Some(media) = &tier.media {
+            metadata.media = Some(media.clone());
+            metadata.media_hash = None;
+        }
+        set_extra_field(metadata, "tier", Value::String(tier.name));
+    }
+}
@@ -1,12 +1,34 @@
 use crate::*;
+use near_sdk::ext_contract;
 
 /// external contract calls
 
-#[ext_contract(ext_contract)]
-trait ExtContract {
-    fn nft_mint_owner(
+#[ext_contract(ext_self)]
+pub trait SelfContract {
+    fn nft_mint_owner(&mut self, receiver_id: AccountId) -> Token;
+
+    fn resolve_redeem(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        target_id: AccountId,
+        redeemer_id: AccountId,
+    ) -> bool;
+}
+
+#[ext_contract(ext_pfp)]
+pub trait PfpDrop {
+    /// Mints or reserves a PFP for `receiver_id` against the whitelist token `whitelist_token_id`.
+    fn nft_claim(&mut self, receiver_id: AccountId, whitelist_token_id: TokenId);
+}
+
+#[ext_contract(ext_approval_receiver)]
+pub trait NonFungibleTokenApprovalReceiver {
+    fn nft_on_approve(
         &mut self,
         token_id: TokenId,
-        receiver_id: ValidAccountId,
+        owner_id: AccountId,
+        approval_id: u64,
+        msg: String,
     );
-}
\ No newline at end of file
+}
@@ -0,0 +1,102 @@
+use crate::events::log_whitelist_event;
+use crate::*;
+use near_sdk::collections::Vector;
+
+/// Holder list frozen by `snapshot_holders`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct HolderSnapshot {
+    pub taken_at: u64,
+    pub holders: Vector<(AccountId, u64)>,
+}
+
+/// Summary of a holder snapshot, see `get_holder_snapshot`.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HolderSnapshotInfo {
+    pub snapshot_id: u64,
+    pub taken_at: U64,
+    pub holder_count: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Number of accounts holding at least one token.
+    pub fn holder_count(&self) -> u64 {
+        self.holders.len()
+    }
+
+    /// Current holders with the number of tokens each one holds.
+    pub fn holders(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<(AccountId, u64)> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let account_ids = self.holders.keys_as_vector();
+        let counts = self.holders.values_as_vector();
+        (start_index as u64..account_ids.len())
+            .take(limit)
+            .map(|index| (account_ids.get(index).unwrap(), counts.get(index).unwrap()))
+            .collect()
+    }
+
+    /// Freezes the current holder list for an airdrop and returns its snapshot id. Later
+    /// transfers don't change the snapshot.
+    pub fn snapshot_holders(&mut self) -> u64 {
+        self.assert_owner();
+        let snapshot_id = self.next_snapshot_id;
+        self.next_snapshot_id += 1;
+
+        let mut holders = Vector::new(StorageKey::HolderSnapshotInner { snapshot_id });
+        for holder in self.holders.iter() {
+            holders.push(&holder);
+        }
+        let snapshot = HolderSnapshot { taken_at: env::block_timestamp(), holders };
+        log_whitelist_event(
+            "holder_snapshot",
+            json!([{ "snapshot_id": snapshot_id, "holder_count": snapshot.holders.len() }]),
+        );
+        self.holder_snapshots.insert(&snapshot_id, &snapshot);
+        snapshot_id
+    }
+
+    pub fn get_holder_snapshot(&self, snapshot_id: u64) -> Option<HolderSnapshotInfo> {
+        self.holder_snapshots.get(&snapshot_id).map(|snapshot| HolderSnapshotInfo {
+            snapshot_id,
+            taken_at: snapshot.taken_at.into(),
+            holder_count: snapshot.holders.len(),
+        })
+    }
+
+    /// Holders recorded by the snapshot `snapshot_id`, with their counts at that time.
+    pub fn holders_at_snapshot(
+        &self,
+        snapshot_id: u64,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, u64)> {
+        let snapshot = self.holder_snapshots.get(&snapshot_id).expect("Snapshot not found");
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        (start_index as u64..snapshot.holders.len())
+            .take(limit)
+            .filter_map(|index| snapshot.holders.get(index))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Refreshes the holder entry of `account_id` from the tokens it currently owns.
+    pub(crate) fn internal_sync_holder(&mut self, account_id: &AccountId) {
+        let count = self
+            .tokens
+            .tokens_per_owner
+            .as_ref()
+            .and_then(|tokens_per_owner| tokens_per_owner.get(account_id))
+            .map_or(0, |owner_tokens| owner_tokens.len());
+        if count == 0 {
+            self.holders.remove(account_id);
+        } else {
+            self.holders.insert(account_id, &count);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::*;
+use near_sdk::serde_json::{self, Map, Value};
+
+impl Contract {
+    /// Panics unless called by the account that owns the collection.
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.tokens.owner_id,
+            "Only the contract owner can call this method"
+        );
+    }
+
+    /// Mints a token numbered by the next value of the token sequence. Every mint path goes
+    /// through here, so sequence numbers are never reused. Tokens of a variant are given an id
+    /// from the collection's template, other tokens need an explicit `token_id` that can't
+    /// clash with generated ids.
+    pub(crate) fn internal_mint(
+        &mut self,
+        token_id: Option<TokenId>,
+        owner_id: ValidAccountId,
+        mut metadata: Option<TokenMetadata>,
+        variant: Option<String>,
+    ) -> Token {
+        assert!(self.token_minted < MAX_NFT_MINT, "Max token quantity is MAX_NFT_MINT");
+        self.last_token_sequence += 1;
+        let token_id = match (token_id, &variant) {
+            (Some(token_id), _) => {
+                self.assert_not_generated_token_id(&token_id);
+                token_id
+            }
+            (None, Some(variant)) => {
+                let edition = self.internal_next_edition(variant);
+                let mut token_id =
+                    self.internal_token_id(self.last_token_sequence, variant, edition);
+                // legacy tokens could be minted with any id, generated ids skip over them
+                while self.tokens.owner_by_id.contains_key(&token_id) {
+                    self.last_token_sequence += 1;
+                    token_id = self.internal_token_id(self.last_token_sequence, variant, edition);
+                }
+                token_id
+            }
+            (None, None) => env::panic(b"Token id is required for tokens without a variant"),
+        };
+        let sequence = self.last_token_sequence;
+        self.token_minted += 1;
+        self.circulating_supply += 1;
+
+        if let Some(variant) = &variant {
+            if let Some(royalty) = self.variant_royalties.get(variant) {
+                self.token_royalties.insert(&token_id, &royalty);
+            }
+            *self.variant_minted.entry(variant.clone()).or_insert(0) += 1;
+            if self.internal_variant_config(variant).soulbound {
+                self.soulbound_tokens.insert(&token_id);
+            }
+        }
+        self.internal_index_token(&token_id, owner_id.as_ref(), sequence, variant.as_deref());
+        self.token_held_since.insert(&token_id, &env::block_timestamp());
+        if let Some(transfer_lock_ns) = self.internal_config().transfer_lock_ns {
+            let unlocks_at = env::block_timestamp().saturating_add(transfer_lock_ns.0);
+            self.transfer_unlocks_at.insert(&token_id, &unlocks_at);
+            if let Some(metadata) = metadata.as_mut() {
+                set_extra_field(metadata, "transfer_unlocks_at", json!(U64(unlocks_at)));
+            }
+        }
+        let token = self.tokens.mint(token_id, owner_id, metadata);
+        self.internal_sync_holder(&token.owner_id);
+        token
+    }
+
+    /// Edition the next token of `variant` gets: editions count from 1 within each variant
+    /// without gaps, burned tokens keeping theirs.
+    pub(crate) fn internal_next_edition(&self, variant: &str) -> u64 {
+        self.variant_minted.get(variant).unwrap_or(&0) + 1
+    }
+
+    /// Panics if `token_id` can't be transferred by its owner or an approved account right
+    /// now. Checked by every transfer its holder asks for, not by escrow moves.
+    pub(crate) fn assert_transferable(&self, token_id: &TokenId) {
+        self.assert_not_soulbound(token_id);
+        self.assert_transfer_unlocked(token_id);
+        self.assert_no_bids(token_id);
+        if self.internal_config().enforce_validity_on_transfer {
+            self.assert_valid_token(token_id);
+        }
+    }
+
+    /// Same checks as `assert_transferable` besides bids, for the auction settlement, which
+    /// can't turn a placed bid down by panicking.
+    pub(crate) fn internal_is_transferable(&self, token_id: &TokenId) -> bool {
+        let unlocked = self
+            .transfer_unlocks_at
+            .get(token_id)
+            .map_or(true, |unlocks_at| env::block_timestamp() >= unlocks_at);
+        let valid = !self.internal_config().enforce_validity_on_transfer
+            || self.internal_validity_error(token_id).is_none();
+        !self.soulbound_tokens.contains(token_id) && unlocked && valid
+    }
+
+    /// Bookkeeping after `token_id` moved from `old_owner_id` to `new_owner_id`. Every transfer
+    /// path goes through here, escrow moves and reverted `nft_transfer_call`s included.
+    pub(crate) fn internal_on_transfer(
+        &mut self,
+        token_id: &TokenId,
+        old_owner_id: &AccountId,
+        new_owner_id: &AccountId,
+    ) {
+        self.token_held_since.insert(token_id, &env::block_timestamp());
+        self.internal_reindex_owner(token_id, old_owner_id, new_owner_id);
+        self.internal_remove_listing(token_id);
+        self.internal_remove_auction(token_id);
+        self.internal_sync_holder(old_owner_id);
+        self.internal_sync_holder(new_owner_id);
+    }
+}
+
+/// Sets `key` in the JSON object kept in the `extra` of `metadata`.
+pub(crate) fn set_extra_field(metadata: &mut TokenMetadata, key: &str, value: Value) {
+    let mut extra: Map<String, Value> = metadata
+        .extra
+        .as_deref()
+        .and_then(|extra| serde_json::from_str(extra).ok())
+        .unwrap_or_default();
+    extra.insert(key.to_string(), value);
+    metadata.extra = Some(Value::Object(extra).to_string());
+}
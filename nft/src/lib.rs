@@ -18,15 +18,58 @@ NOTES:
 use near_contract_standards::non_fungible_token::metadata::{
     NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
 };
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
 use near_contract_standards::non_fungible_token::{Token, TokenId};
 use near_contract_standards::non_fungible_token::NonFungibleToken;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption};
-use near_sdk::json_types::ValidAccountId;
+use near_sdk::collections::{
+    LazyOption, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet,
+};
+use near_sdk::json_types::{ValidAccountId, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    env, near_bindgen, AccountId, BorshStorageKey, PanicOnDefault, Promise, PromiseOrValue, Gas
+    assert_one_yocto, env, near_bindgen, AccountId, Balance, BorshStorageKey, PanicOnDefault, Promise, PromiseOrValue,
+    Gas, StorageUsage,
 };
 use near_sdk::serde_json::json;
+use crate::external::ext_self;
+use std::collections::HashMap;
+
+pub use crate::auction::*;
+pub use crate::config::*;
+pub use crate::enumerations::*;
+pub use crate::evolution::*;
+pub use crate::holders::*;
+pub use crate::market::*;
+pub use crate::metadata::*;
+pub use crate::redeem::*;
+pub use crate::royalty::*;
+pub use crate::stats::*;
+pub use crate::token_metadata::*;
+
+mod approval;
+mod auction;
+mod burn;
+mod config;
+mod enumerations;
+mod events;
+mod evolution;
+mod external;
+mod holders;
+mod internal;
+mod market;
+mod metadata;
+mod migrate;
+mod nft_core;
+mod redeem;
+mod render;
+mod royalty;
+mod soulbound;
+mod stats;
+mod storage;
+mod token_metadata;
+mod transfer_lock;
+mod validity;
 
 near_sdk::setup_alloc!();
 
@@ -35,20 +78,92 @@ near_sdk::setup_alloc!();
 pub struct Contract {
     tokens: NonFungibleToken,
     metadata: LazyOption<NFTContractMetadata>,
-    pub token_minted: u16,
-    pub token_minted_users: u16,
+    pub token_minted: u64,
+    pub token_minted_users: u64,
     current_index: u8,
+    /// Tokens minted and not burned yet.
+    circulating_supply: u64,
+    /// Collection-wide royalty in basis points, paid out on every token.
+    royalty: HashMap<AccountId, u32>,
+    /// Royalty overrides for guest artists, keyed by variant letter.
+    variant_royalties: LookupMap<String, HashMap<AccountId, u32>>,
+    /// Overrides snapshotted from `variant_royalties` when the token was minted.
+    token_royalties: LookupMap<TokenId, HashMap<AccountId, u32>>,
+    /// PFP drop contract that whitelist tokens are redeemed against.
+    pfp_contract_id: Option<AccountId>,
+    /// Tokens whose whitelist right was used, burned through `redeem` or marked by a redeemer.
+    redemptions: LookupMap<TokenId, Redemption>,
+    /// Contracts allowed to mark tokens as redeemed without burning them.
+    redeemers: UnorderedSet<AccountId>,
+    /// NEP-145 storage balances available to pay for approvals and transfers.
+    storage_deposits: LookupMap<AccountId, Balance>,
+    /// Storage taken by the registration of an account with the longest possible id.
+    storage_registration_bytes: StorageUsage,
+    /// Live tokens keyed by their position in the mint order.
+    tokens_by_sequence: TreeMap<u64, TokenId>,
+    /// Position of each live token in the mint order, used as its pagination cursor.
+    token_sequence: LookupMap<TokenId, u64>,
+    /// Variant letter of each live token.
+    token_variants: LookupMap<TokenId, String>,
+    /// Live tokens of each variant keyed by their position in the mint order.
+    variant_tokens: LookupMap<String, TreeMap<u64, TokenId>>,
+    /// Live tokens of each owner keyed by their position in the mint order.
+    owner_tokens: LookupMap<AccountId, TreeMap<u64, TokenId>>,
+    /// Tokens minted so far for each variant letter, burned ones included.
+    variant_minted: HashMap<String, u64>,
+    /// Deposits received by `nft_mint`.
+    proceeds_collected: Balance,
+    /// Part of `proceeds_collected` sent out with `withdraw_proceeds`.
+    proceeds_withdrawn: Balance,
+    /// Number of tokens held by every account owning at least one.
+    holders: UnorderedMap<AccountId, u64>,
+    /// Holder lists frozen by `snapshot_holders`.
+    holder_snapshots: LookupMap<u64, HolderSnapshot>,
+    next_snapshot_id: u64,
+    /// Last number handed out by the token sequence, shared by every mint path.
+    last_token_sequence: u64,
+    /// Progress of `backfill_legacy_tokens`, `None` once every legacy token is indexed.
+    legacy_backfill: Option<migrate::LegacyBackfill>,
+    /// Collection settings, see `CollectionConfig`.
+    config: LazyOption<CollectionConfig>,
+    /// Accounts allowed to update and lock token metadata besides the owner.
+    metadata_updaters: UnorderedSet<AccountId>,
+    /// Tokens whose metadata can't be updated anymore.
+    locked_token_metadata: LookupSet<TokenId>,
+    /// Whether the metadata of every token is locked.
+    token_metadata_locked: bool,
+    /// Set by `freeze_metadata`, after which no metadata can change.
+    metadata_frozen: bool,
+    /// When each token was last minted or transferred, for the evolution tiers.
+    token_held_since: LookupMap<TokenId, u64>,
+    /// `token_held_since` of tokens sent with `nft_transfer_call` or escrowed by `redeem`, put
+    /// back if the token returns to its owner.
+    held_since_in_transfer: LookupMap<TokenId, u64>,
+    /// Tokens that can't be transferred or approved, see `set_token_transferable`.
+    soulbound_tokens: LookupSet<TokenId>,
+    /// When tokens minted during a transfer lock can first be transferred.
+    transfer_unlocks_at: LookupMap<TokenId, u64>,
+    /// Tokens listed for sale through `buy`.
+    listings: UnorderedMap<TokenId, Listing>,
+    listings_by_seller: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    /// Running and unsettled auctions, see `start_auction`.
+    auctions: UnorderedMap<TokenId, Auction>,
 }
 
 const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml,%3Csvg id='SVG' xmlns='http://www.w3.org/2000/svg' width='500' height='500'%3E%%3Cpath class='cls-1' fill='%2300b7b2' stroke='%23fff' stroke-width='4.43px' fill-rule='evenodd' d='M8.634,486.008V158.294H282.493l30.012,32.367V158.294H490.076V486.008H197.46L163.7,449.6v36.413H8.634Z'/%3E%3Cpath id='Comics_copy_2' data-name='Comics copy 2' class='cls-2' stroke='%23000' stroke-linejoin='round' stroke-width='1px' fill='%23ff6000' d='M76.724,293.832q-8.535-4.329-21.887-.337-21.015,6.282-35.884,27.189Q4.993,340.24,4.994,359.4q0,17,12.442,26.976,13.275,10.468,32.091,4.843,6.6-1.973,15.552-8.36Q75.4,375.66,75.4,370.531a5.391,5.391,0,0,0-2.579-4.829,7.032,7.032,0,0,0-6.069-.614q-2.884.863-8,4.687t-7.928,4.664q-10.091,3.016-17.373-3.779a20.736,20.736,0,0,1-6.448-15.545q0-11.739,7.511-24.036,8.572-13.695,21.09-17.437,6.6-1.974,6.6,2.479a22.311,22.311,0,0,1-.91,5.062,22.329,22.329,0,0,0-.91,5.062,6.6,6.6,0,0,0,3.262,6.041,9.108,9.108,0,0,0,7.662.678q6.6-1.974,10.621-9.854a28.783,28.783,0,0,0,3.338-13.074Q85.259,298.162,76.724,293.832Zm103.669-4.616q-1.442-13.263-10.773-19.919-10.242-7.326-25.263-2.836-21.621,6.463-37.1,29.981-14.414,21.715-14.414,41.617,0,16.933,12.214,24.885,12.366,8.313,31.181,2.687,22.378-6.691,34.442-28.985a81.56,81.56,0,0,0,10.09-39.852,65.53,65.53,0,0,0-.379-7.578h0Zm-22.684,44.967q-6.6,11.419-17.6,14.707a26.177,26.177,0,0,1-14.377.52,14.757,14.757,0,0,1-9.672-7.093,22.019,22.019,0,0,1-2.352-10.7q0-9.918,5.69-21.232a61.411,61.411,0,0,1,15.021-19.3,26.954,26.954,0,0,0,5.993-2.4,48.78,48.78,0,0,1,6.145-2.714,3.427,3.427,0,0,1,1.745-.05q7.511,1.533,11.608,11.035a41.547,41.547,0,0,1,3.413,17.059,40.223,40.223,0,0,1-5.614,20.164h0ZM272.417,228.444q-7.132,2.133-12.29,11.838-2.049,3.918-6.069,15.644-6.45,18.459-7.663,22.4-4.628,16.834-6.676,25.136-9.711-25.092-14.262-37.833a77.745,77.745,0,0,0-4.7-9.928q-6.6-10.236-16.387-7.311a18.336,18.336,0,0,0-7.928,4.934q-3.526,3.618-3.527,7.531,0,4.318.8,12.715t0.8,12.782q0,8.771-.379,14.888-0.3,4.478-1.973,18.737a164.04,164.04,0,0,0-1.441,18.782q0,11.064,8.724,8.455a11.6,11.6,0,0,0,5.8-3.894,9.337,9.337,0,0,0,2.314-5.954q0-.876-0.114-2.8t-0.114-2.867q0-11.536,5.311-36.8,21.317,40.38,29.359,37.975a9.667,9.667,0,0,0,4.59-3.126,7.231,7.231,0,0,0,2.01-4.649,3.7,3.7,0,0,0-.227-1.147q4.248-9.5,5.917-14.25,7.131-18.12,10.925-27.215,0.606,21,.607,26.2,0,8.433.758,10.972,1.821,5.865,8.573,3.846a12.067,12.067,0,0,0,5.842-3.8,8.914,8.914,0,0,0,2.351-5.864q0-2.293-.189-6.758t-0.19-6.757a130.26,130.26,0,0,1,.986-14.192q1.593-12.687,1.669-14.464,0.91-13.494,1.29-20.288,0.911-9.918.91-11.538,0-15.989-15.4-11.385h0Zm49.16,11.074q-0.226-11.2-1.9-16.164-3.414-8.829-12.594-6.085-9.939,2.971-9.938,13.9,0,2.5,2.807,15.284,2.5,10.99,2.5,31.7,0,5.2-.91,15.822t-0.91,15.823a4.854,4.854,0,0,0,2.2,4.47,6.59,6.59,0,0,0,5.538.368q7.283-2.178,9.331-10.751,0.529-2.385.911-13.293,0.3-6.093,1.669-21.21,1.44-13.72,1.441-21.277,0-2.091-.152-8.59h0Zm80.076-42.83q-8.535-4.331-21.887-.338-21.017,6.283-35.884,27.19-13.961,19.554-13.96,38.714,0,17,12.442,26.977,13.275,10.468,32.091,4.843,6.6-1.974,15.552-8.361,10.317-7.2,10.318-12.327a5.389,5.389,0,0,0-2.58-4.828,7.025,7.025,0,0,0-6.069-.614q-2.883.863-8,4.686t-7.928,4.664q-10.091,3.018-17.222-3.824a20.361,20.361,0,0,1-6.6-15.5q0-11.739,7.511-24.037,8.572-13.693,21.09-17.437,6.6-1.973,6.6,2.48a22.308,22.308,0,0,1-.91,5.062,22.308,22.308,0,0,0-.91,5.062,6.6,6.6,0,0,0,3.262,6.041,9.11,9.11,0,0,0,7.662.677q6.6-1.972,10.621-9.854a28.779,28.779,0,0,0,3.338-13.074Q410.187,201.019,401.653,196.688ZM487.715,169.2q-6.753-3.107-16.311-.25-16.463,4.922-31.18,18.294-16.312,14.862-16.311,28.894,0,10.188,11.531,16.859,6.675,3.942,21.242,8.154,11.455,3.255,11.456,4.873,0,4.251-11.987,7.834a31.085,31.085,0,0,1-15.324.331,7.493,7.493,0,0,0-3.338-.148q-3.264.975-5.842,6.873a32.147,32.147,0,0,0-2.428,8.349q12.517,5.5,27.843.919A54.083,54.083,0,0,0,478,258.123q10.771-9.628,10.773-19.345,0-8.971-10.773-14.522-2.807-1.453-20.18-7.189-10.773-3.593-10.773-7.844,0-4.385,10.09-11.652,8.952-6.453,14.642-8.155a1.175,1.175,0,0,0,.38.021q2.5,9.507,11.91,6.694a14.2,14.2,0,0,0,8.346-6.341A16.2,16.2,0,0,0,495,180.721q0-8.231-7.283-11.518h0Z'/%3E%3Cpath class='cls-1' fill='%2300b7b2' stroke='%23fff' stroke-width='4.43px' fill-rule='evenodd'  d='M8.634,148.854V13.992H490.076V148.854H8.634Z'/%3E%3Cpath id='NEARHUB' class='cls-3' stroke='%23000' stroke-linejoin='round' stroke-width='1px' fill='%23fff' d='M45,128.625v-26.27c0-16.577-.26-30.344-0.912-42h0.391C48.129,70.606,53.34,82.687,57.9,92.24l17.064,36.385H94.241V33.941H77.047V59.508c0,15.312.521,29.08,1.563,40.88H78.35a293.219,293.219,0,0,0-12.9-31.046l-16.8-35.4H27.809v94.685H45ZM145.564,71.308H116.907V50.939h30.22v-17H97.889v94.685h51.062v-17H116.907V88.166h28.657V71.308Zm44.158,33.856,5.862,23.461h19.93L191.285,33.941H166.927l-24.359,94.685h19.278l5.471-23.461h22.405Zm-19.8-15.453,4.56-20.089c1.3-5.479,2.6-13.486,3.777-19.246h0.261c1.3,5.76,2.735,13.627,4.038,19.246l4.689,20.089H169.922Zm43.768,38.914h18.888V91.257H237.4c7.425,0.141,10.942,3.372,13.026,15.172,2.215,11.238,4.3,19.526,5.6,22.2h19.539c-1.694-3.653-4.3-15.734-6.644-26.411-2.084-9.131-5.34-15.312-11.463-17.981V83.811a25.028,25.028,0,0,0,14.98-23.32c0-8.569-2.475-15.172-7.815-19.808-6.123-5.479-14.98-7.445-26.834-7.445a138.445,138.445,0,0,0-24.1,1.967v93.42Zm18.888-79.232A36.249,36.249,0,0,1,240,48.832c8.727,0,13.416,4.917,13.416,13.346,0,8.288-5.34,14.048-14.459,14.048h-6.382V49.393Zm40.511-15.453v94.685h19.018V89.009h28.266v39.616h19.018V33.941H320.373V70.887H292.107V33.941H273.089Zm69.689,0V88.447c0,28.237,11.2,41.582,32.174,41.582,21.233,0,33.347-13.626,33.347-41.441V33.941H389.281V90.554c0,16.015-5.08,22.758-13.808,22.758-8.467,0-13.677-7.164-13.677-22.758V33.941H342.778ZM411.686,128.2a124.742,124.742,0,0,0,19.8,1.405c16.543,0,26.7-3.231,32.826-8.991a26.167,26.167,0,0,0,8.206-19.667c0-11.52-6.773-20.089-16.8-23.039V77.49c9.51-3.933,13.808-11.941,13.808-20.229,0-8.007-3.517-14.47-9.248-18.263-6.253-4.5-13.938-5.76-24.88-5.76-9.118,0-18.5.843-23.707,1.967v93Zm18.887-79.231a26.628,26.628,0,0,1,7.165-.7c8.467,0,12.895,4.355,12.895,11.379,0,7.305-5.21,12.222-14.459,12.222h-5.6v-22.9Zm0,37.789h5.862c8.858,0,16.282,4.074,16.282,13.627,0,9.834-7.294,13.767-15.761,13.767a40.259,40.259,0,0,1-6.383-.281V86.761Z'/%3E%3C/svg%3E";
-const MAX_NFT_MINT: u16 = 500;
-const MAX_NFT_MINT_USERS: u16 = 300;
+const MAX_NFT_MINT: u64 = 500;
+const MAX_NFT_MINT_USERS: u64 = 300;
+/// Tokens minted per variant, the five variants sharing `MAX_NFT_MINT` evenly.
+const VARIANT_EDITION_SIZE: u64 = MAX_NFT_MINT / 5;
+/// Gateway the images were first published on, the `base_uri` until the owner sets another.
+const DEFAULT_BASE_URI: &str = "https://cloudflare-ipfs.com/ipfs";
+/// Image of each variant, relative to `base_uri`.
 const NFT_IMAGES: [&str; 5] = [
-        "https://cloudflare-ipfs.com/ipfs/bafybeie6tdmf5whxd4sy4b7wtnzjafja4pgvlsah2jxknd6wxgtjzqngvy/WL%201%20HRMS%20copy.jpg",
-        "https://cloudflare-ipfs.com/ipfs/bafybeigrw46fpw3wldc4jdwwpabuift4nk4egkdqdui5dqidfxdon3vgnq/WL%202%20HRMS%20copy.jpg",  
-        "https://cloudflare-ipfs.com/ipfs/bafybeigllxpu5lwak6hilfojc4dssi43pxajhf3lnichxvut6lwf3ekjsm/WL%203%20HRMS%20copy.jpg", 
-        "https://cloudflare-ipfs.com/ipfs/bafybeifz7txlqaghmd65xuf3pm6h2sqp2j7szerellxmyxpho74ao7yzcu/WL%204%20HRMS%20copy.jpg", 
-        "https://cloudflare-ipfs.com/ipfs/bafybeiejustedpnpl2sl37dvmifszj6xazi6rc7hdulc744nqtkyii7tdi/WL%205%20HRMS%20copy.jpg"];
+        "bafybeie6tdmf5whxd4sy4b7wtnzjafja4pgvlsah2jxknd6wxgtjzqngvy/WL%201%20HRMS%20copy.jpg",
+        "bafybeigrw46fpw3wldc4jdwwpabuift4nk4egkdqdui5dqidfxdon3vgnq/WL%202%20HRMS%20copy.jpg",
+        "bafybeigllxpu5lwak6hilfojc4dssi43pxajhf3lnichxvut6lwf3ekjsm/WL%203%20HRMS%20copy.jpg",
+        "bafybeifz7txlqaghmd65xuf3pm6h2sqp2j7szerellxmyxpho74ao7yzcu/WL%204%20HRMS%20copy.jpg",
+        "bafybeiejustedpnpl2sl37dvmifszj6xazi6rc7hdulc744nqtkyii7tdi/WL%205%20HRMS%20copy.jpg"];
 const NFT_IMAGE_HASHES: [&str; 5] = [
         "5c9fddd986a2453a482cbd7e541107a023145b7538b6fe0b8c7cbe4fb79dbdfd",
         "87393da5cbdb077e68d4e15a14c423c70e6921cbf5c859792f0ad6a5c7d6b585", 
@@ -67,6 +182,32 @@ enum StorageKey {
     TokenMetadata,
     Enumeration,
     Approval,
+    VariantRoyalties,
+    TokenRoyalties,
+    Redemptions,
+    Redeemers,
+    StorageDeposits,
+    TokensBySequence,
+    TokenSequence,
+    TokenVariants,
+    VariantTokens,
+    VariantTokensInner { variant: String },
+    Holders,
+    HolderSnapshots,
+    HolderSnapshotInner { snapshot_id: u64 },
+    CollectionConfig,
+    MetadataUpdaters,
+    LockedTokenMetadata,
+    TokenHeldSince,
+    SoulboundTokens,
+    TransferUnlocksAt,
+    Listings,
+    ListingsBySeller,
+    ListingsBySellerInner { account_id: AccountId },
+    Auctions,
+    OwnerTokens,
+    OwnerTokensInner { account_id: AccountId },
+    HeldSinceInTransfer,
 }
 
 #[near_bindgen]
@@ -82,30 +223,35 @@ impl Contract {
                 name: "Near Hub NFT Comics".to_string(),
                 symbol: "NHNFTC".to_string(),
                 icon: Some(DATA_IMAGE_SVG_NEAR_ICON.to_string()),
-                base_uri: None,
+                base_uri: Some(DEFAULT_BASE_URI.to_string()),
                 reference: None,
                 reference_hash: None,
             },
         )
     }
 
+    /// Initializes the contract owned by `owner_id`. Token media is stored relative to
+    /// `metadata.base_uri`, which defaults to `DEFAULT_BASE_URI`.
     #[init]
     pub fn new(owner_id: ValidAccountId, metadata: NFTContractMetadata) -> Self {
         assert!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
-        Self {
-            tokens: NonFungibleToken::new(
+        let metadata = NFTContractMetadata {
+            base_uri: metadata.base_uri.or_else(|| Some(DEFAULT_BASE_URI.to_string())),
+            ..metadata
+        };
+        let mut this = Self::from_parts(
+            NonFungibleToken::new(
                 StorageKey::NonFungibleToken,
                 owner_id,
                 Some(StorageKey::TokenMetadata),
                 Some(StorageKey::Enumeration),
                 Some(StorageKey::Approval),
             ),
-            metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
-            token_minted: 0,
-            token_minted_users: 0,
-            current_index: 0,
-        }
+            LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+        );
+        this.measure_storage_registration();
+        this
     }
 
     /// Mint a new token with ID=`token_id` belonging to `receiver_id`.
@@ -132,15 +278,14 @@ impl Contract {
             self.token_minted_users < MAX_NFT_MINT_USERS,
             "Max token on sale is MAX_NFT_MINT_USERS"
         );
+        self.proceeds_collected += env::attached_deposit();
 
         let remaining_gas: Gas = env::prepaid_gas() - env::used_gas() - GAS_RESERVED_FOR_CURRENT_CALL;
-        Promise::new(env::current_account_id()).function_call(
-            b"nft_mint_owner".to_vec(),
-            json!({ "receiver_id": env::signer_account_id().to_string() }) // method arguments
-                .to_string()
-                .into_bytes(),
+        ext_self::nft_mint_owner(
+            env::signer_account_id(),
+            &env::current_account_id(),
             75_000_000_000_000_000_000_000,    // amount of yoctoNEAR to attach
-            remaining_gas)       // gas to attach)
+            remaining_gas)       // gas to attach
     }
 
     #[payable]
@@ -160,30 +305,38 @@ impl Contract {
         if self.current_index > 4 {
             self.current_index = 0
         }
-        let url = NFT_IMAGES[self.current_index as usize];
-        let l: String;
-        match self.current_index {
-            0 => l = String::from("a"),
-            1 => l = String::from("b"),
-            2 => l = String::from("c"),
-            3 => l = String::from("d"),
-            4 => l = String::from("e"),
-            _ => l = String::from("e"),
-        }
-        let title: String =format!("HRMS #1{} Whitelist NFTs", l);
+        let image = NFT_IMAGES[self.current_index as usize];
+        let l = match self.current_index {
+            0 => String::from("a"),
+            1 => String::from("b"),
+            2 => String::from("c"),
+            3 => String::from("d"),
+            _ => String::from("e"),
+        };
+        let edition = self.internal_next_edition(&l);
+        let title: String = format!("HRMS #1{} — {}/{}", l, edition, VARIANT_EDITION_SIZE);
+        let variant_config = self.internal_variant_config(&l);
+        let config = self.internal_config();
+        let url = variant_config.media.unwrap_or_else(|| image.to_string());
+        let extra = json!({
+            "variant": l,
+            "edition": edition,
+            "edition_size": VARIANT_EDITION_SIZE,
+            "attributes": variant_config.attributes,
+        });
         let _metadata = TokenMetadata {
-            title: Some(title.into()),
-            description: Some("NFTs created to participate in the whitelist portion of the NEARHUB Comic issue #1 PFP NFT drop.".into()),
-            media: Some(url.to_string()),
+            title: Some(title),
+            description: Some("NFTs created to participate in the whitelist portion of the NEARHUB Comic issue #1 PFP NFT drop.".to_string()),
+            media: Some(url),
             media_hash: None,
-            copies: Some(100u64),
+            copies: Some(VARIANT_EDITION_SIZE),
             issued_at: Some(env::block_timestamp().to_string()),
-            expires_at: None,
-            starts_at: None,
+            expires_at: config.expires_at.map(|expires_at| expires_at.0.to_string()),
+            starts_at: config.starts_at.map(|starts_at| starts_at.0.to_string()),
             updated_at: None,
-            extra: None,
-            reference: None,
-            reference_hash: None,
+            extra: Some(extra.to_string()),
+            reference: variant_config.reference,
+            reference_hash: variant_config.reference_hash,
         };
         if self.current_index == 4 {
             self.current_index = 0
@@ -192,37 +345,101 @@ impl Contract {
             self.current_index += 1;
         }
         self.current_index += 1;
-        self.token_minted += 1;
         if env::current_account_id() != env::signer_account_id() {
             self.token_minted_users += 1;
         }
-        self.tokens.mint(self.token_minted.to_string(), receiver_id, Some(_metadata))
+        self.internal_mint(None, receiver_id, Some(_metadata), Some(l))
     }
 
-    pub fn get_user_minted_quantity(&self) -> u16 {
+    pub fn get_user_minted_quantity(&self) -> u64 {
         self.token_minted_users
     }
+
+    /// Sends `amount` of the mint proceeds not withdrawn yet, or all of them, to `receiver_id`.
+    /// The contract keeps enough to cover its storage, mint storage having been paid from the
+    /// proceeds.
+    #[payable]
+    pub fn withdraw_proceeds(&mut self, receiver_id: ValidAccountId, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        let storage_cost = Balance::from(env::storage_usage()) * env::storage_byte_cost();
+        let available = (self.proceeds_collected - self.proceeds_withdrawn)
+            .min(env::account_balance().saturating_sub(storage_cost));
+        let amount = amount.map_or(available, u128::from);
+        assert!(amount > 0, "Nothing to withdraw");
+        assert!(amount <= available, "Amount exceeds the proceeds available");
+        self.proceeds_withdrawn += amount;
+        Promise::new(receiver_id.into()).transfer(amount)
+    }
 }
 
-near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
-near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
-near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
+impl Contract {
+    /// Contract around existing token and metadata storage, with every other field empty.
+    fn from_parts(tokens: NonFungibleToken, metadata: LazyOption<NFTContractMetadata>) -> Self {
+        Self {
+            tokens,
+            metadata,
+            token_minted: 0,
+            token_minted_users: 0,
+            current_index: 0,
+            circulating_supply: 0,
+            royalty: HashMap::new(),
+            variant_royalties: LookupMap::new(StorageKey::VariantRoyalties),
+            token_royalties: LookupMap::new(StorageKey::TokenRoyalties),
+            pfp_contract_id: None,
+            redemptions: LookupMap::new(StorageKey::Redemptions),
+            redeemers: UnorderedSet::new(StorageKey::Redeemers),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            storage_registration_bytes: 0,
+            tokens_by_sequence: TreeMap::new(StorageKey::TokensBySequence),
+            token_sequence: LookupMap::new(StorageKey::TokenSequence),
+            token_variants: LookupMap::new(StorageKey::TokenVariants),
+            variant_tokens: LookupMap::new(StorageKey::VariantTokens),
+            owner_tokens: LookupMap::new(StorageKey::OwnerTokens),
+            variant_minted: HashMap::new(),
+            proceeds_collected: 0,
+            proceeds_withdrawn: 0,
+            holders: UnorderedMap::new(StorageKey::Holders),
+            holder_snapshots: LookupMap::new(StorageKey::HolderSnapshots),
+            next_snapshot_id: 0,
+            last_token_sequence: 0,
+            legacy_backfill: None,
+            config: LazyOption::new(StorageKey::CollectionConfig, None),
+            metadata_updaters: UnorderedSet::new(StorageKey::MetadataUpdaters),
+            locked_token_metadata: LookupSet::new(StorageKey::LockedTokenMetadata),
+            token_metadata_locked: false,
+            metadata_frozen: false,
+            token_held_since: LookupMap::new(StorageKey::TokenHeldSince),
+            held_since_in_transfer: LookupMap::new(StorageKey::HeldSinceInTransfer),
+            soulbound_tokens: LookupSet::new(StorageKey::SoulboundTokens),
+            transfer_unlocks_at: LookupMap::new(StorageKey::TransferUnlocksAt),
+            listings: UnorderedMap::new(StorageKey::Listings),
+            listings_by_seller: LookupMap::new(StorageKey::ListingsBySeller),
+            auctions: UnorderedMap::new(StorageKey::Auctions),
+        }
+    }
+}
 
 #[near_bindgen]
 impl NonFungibleTokenMetadataProvider for Contract {
     fn nft_metadata(&self) -> NFTContractMetadata {
-        self.metadata.get().unwrap()
+        self.internal_metadata()
     }
 }
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::testing_env;
+    use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+    use near_contract_standards::non_fungible_token::bytes_for_approved_account_id;
+    use near_contract_standards::non_fungible_token::core::NonFungibleTokenResolver;
+    use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::test_utils::{accounts, testing_env_with_promise_results, VMContextBuilder};
+    use near_sdk::{testing_env, MockedBlockchain, PromiseResult};
 
     use super::*;
 
-    const MINT_STORAGE_COST: u128 = 5870000000000000000000;
+    const MINT_STORAGE_COST: u128 = 75_000_000_000_000_000_000_000;
 
     fn get_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
@@ -233,18 +450,35 @@ mod tests {
         builder
     }
 
+    /// Mints the next token to `receiver_id` as the contract owner.
+    fn mint_to(
+        context: &mut VMContextBuilder,
+        contract: &mut Contract,
+        receiver_id: ValidAccountId,
+    ) -> Token {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_mint_owner(receiver_id)
+    }
+
     fn sample_token_metadata() -> TokenMetadata {
         TokenMetadata {
-            title: Some("Olympus Mons".into()),
-            description: Some("The tallest mountain in the charted solar system".into()),
-            media: None,
+            title: Some("HRMS #1a — 1/100".into()),
+            description: Some("NFTs created to participate in the whitelist portion of the NEARHUB Comic issue #1 PFP NFT drop.".into()),
+            media: Some(NFT_IMAGES[0].into()),
             media_hash: None,
-            copies: Some(1u64),
-            issued_at: None,
+            copies: Some(100u64),
+            issued_at: Some("0".into()),
             expires_at: None,
             starts_at: None,
             updated_at: None,
-            extra: None,
+            extra: Some(
+                json!({ "variant": "a", "edition": 1, "edition_size": 100, "attributes": [] })
+                    .to_string(),
+            ),
             reference: None,
             reference_hash: None,
         }
@@ -279,8 +513,8 @@ mod tests {
             .predecessor_account_id(accounts(0))
             .build());
 
-        let token_id = "0".to_string();
-        let token = contract.nft_mint();
+        let token_id = "1".to_string();
+        let token = contract.nft_mint_owner(accounts(0));
         assert_eq!(token.token_id, token_id);
         assert_eq!(token.owner_id, accounts(0).to_string());
         assert_eq!(token.metadata.unwrap(), sample_token_metadata());
@@ -298,8 +532,8 @@ mod tests {
             .attached_deposit(MINT_STORAGE_COST)
             .predecessor_account_id(accounts(0))
             .build());
-        let token_id = "0".to_string();
-        contract.nft_mint();
+        let token_id = "1".to_string();
+        contract.nft_mint_owner(accounts(0));
 
         testing_env!(context
             .storage_usage(env::storage_usage())
@@ -314,6 +548,9 @@ mod tests {
             .is_view(true)
             .attached_deposit(0)
             .build());
+        assert!(contract.nft_tokens_for_owner_page(accounts(0), None, None).tokens.is_empty());
+        let page = contract.nft_tokens_for_owner_page(accounts(1), None, None);
+        assert_eq!(page.tokens[0].token_id, token_id);
         if let Some(token) = contract.nft_token(token_id.clone()) {
             assert_eq!(token.token_id, token_id);
             assert_eq!(token.owner_id, accounts(1).to_string());
@@ -335,8 +572,8 @@ mod tests {
             .attached_deposit(MINT_STORAGE_COST)
             .predecessor_account_id(accounts(0))
             .build());
-        let token_id = "0".to_string();
-        contract.nft_mint();
+        let token_id = "1".to_string();
+        contract.nft_mint_owner(accounts(0));
 
         // alice approves bob
         testing_env!(context
@@ -366,8 +603,8 @@ mod tests {
             .attached_deposit(MINT_STORAGE_COST)
             .predecessor_account_id(accounts(0))
             .build());
-        let token_id = "0".to_string();
-        contract.nft_mint();
+        let token_id = "1".to_string();
+        contract.nft_mint_owner(accounts(0));
 
         // alice approves bob
         testing_env!(context
@@ -404,8 +641,8 @@ mod tests {
             .attached_deposit(MINT_STORAGE_COST)
             .predecessor_account_id(accounts(0))
             .build());
-        let token_id = "0".to_string();
-        contract.nft_mint();
+        let token_id = "1".to_string();
+        contract.nft_mint_owner(accounts(0));
 
         // alice approves bob
         testing_env!(context
@@ -430,4 +667,1618 @@ mod tests {
             .build());
         assert!(!contract.nft_is_approved(token_id.clone(), accounts(1), Some(1)));
     }
+
+    #[test]
+    fn test_payout_merges_variant_royalty() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let mut royalty = HashMap::new();
+        royalty.insert(accounts(2).to_string(), 500);
+        contract.set_royalty(royalty);
+        let mut guest_royalty = HashMap::new();
+        guest_royalty.insert(accounts(3).to_string(), 1_000);
+        contract.set_variant_royalty("a".to_string(), guest_royalty);
+
+        let token_id = "1".to_string();
+        mint_to(&mut context, &mut contract, accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        assert_eq!(contract.nft_royalty(token_id.clone()).len(), 2);
+        let payout = contract.nft_payout(token_id, U128(10_000), 3).payout;
+        assert_eq!(payout.get(&accounts(2).to_string()), Some(&U128(500)));
+        assert_eq!(payout.get(&accounts(3).to_string()), Some(&U128(1_000)));
+        assert_eq!(payout.get(&accounts(0).to_string()), Some(&U128(8_500)));
+    }
+
+    #[test]
+    fn test_burn() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = "1".to_string();
+        mint_to(&mut context, &mut contract, accounts(1));
+        assert_eq!(contract.nft_circulating_supply(), 1);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_burn(token_id.clone());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        assert_eq!(contract.nft_circulating_supply(), 0);
+        assert!(contract.nft_tokens_for_owner(accounts(1), None, None).is_empty());
+        assert!(contract.nft_token(token_id).is_none());
+    }
+
+    #[test]
+    fn test_burn_refunds_only_approvals() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(10_000_000_000_000_000_000_000)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.storage_deposit(None, None);
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_approve("1".to_string(), accounts(2), None);
+        let available = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+
+        // the token itself was paid by the contract, so only the approval comes back
+        contract.nft_burn("1".to_string());
+        let approval_cost = Balance::from(bytes_for_approved_account_id(&accounts(2).to_string()))
+            * env::storage_byte_cost();
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(balance.available.0, available + approval_cost);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner or an approved account can burn the token")]
+    fn test_burn_unauthorized() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.nft_burn("1".to_string());
+    }
+
+    #[test]
+    fn test_mark_redeemed() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.add_redeemer(accounts(2));
+
+        let token_id = "1".to_string();
+        mint_to(&mut context, &mut contract, accounts(1));
+        assert!(!contract.is_redeemed(token_id.clone()));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        assert!(contract.mark_redeemed(token_id.clone()));
+        assert!(!contract.mark_redeemed(token_id.clone()));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .build());
+        assert!(contract.is_redeemed(token_id.clone()));
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(token.owner_id, accounts(1).to_string());
+        let extra: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&token.metadata.unwrap().extra.unwrap()).unwrap();
+        assert_eq!(extra["redeemed"], true);
+        assert_eq!(extra["edition"], 1);
+    }
+
+    #[test]
+    fn test_approve_from_storage_balance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = "1".to_string();
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        // bob prepays storage once...
+        let deposit = 10_000_000_000_000_000_000_000;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(deposit)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let balance = contract.storage_deposit(None, None);
+        assert_eq!(balance.total.0, deposit);
+        let available = balance.available.0;
+
+        // ...then approves charlie with a single yoctoNEAR
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_approve(token_id.clone(), accounts(2), None);
+        let approval_cost = Balance::from(bytes_for_approved_account_id(&accounts(2).to_string()))
+            * env::storage_byte_cost();
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(balance.available.0, available + 1 - approval_cost);
+
+        // revoking credits the approval back
+        contract.nft_revoke(token_id.clone(), accounts(2));
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(balance.available.0, available + 1);
+        assert!(!contract.nft_is_approved(token_id, accounts(2), None));
+    }
+
+    #[test]
+    fn test_transfer_credits_approvals_to_storage_balance() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = "1".to_string();
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(10_000_000_000_000_000_000_000)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.storage_deposit(None, None);
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_approve(token_id.clone(), accounts(2), None);
+        let available = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let approval_bytes = bytes_for_approved_account_id(&accounts(2).to_string());
+        let initial_storage_usage = env::storage_usage() - approval_bytes;
+        contract.nft_transfer(accounts(3), token_id, None, None);
+        // the approval comes back once, the receiver's token set is drawn from the balance
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(
+            balance.available.0,
+            available + Balance::from(approval_bytes) * env::storage_byte_cost()
+                - Balance::from(storage_used) * env::storage_byte_cost()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't unregister an account that still owns tokens without force")]
+    fn test_storage_unregister_with_tokens() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(10_000_000_000_000_000_000_000)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.storage_deposit(None, None);
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.storage_unregister(None);
+    }
+
+    #[test]
+    fn test_cursor_pages_skip_burned_tokens() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        // variants cycle a, c, e, b, d
+        for _ in 0..5 {
+            mint_to(&mut context, &mut contract, accounts(1));
+        }
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_burn("2".to_string());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        let ids = |page: &TokenPage| -> Vec<TokenId> {
+            page.tokens.iter().map(|token| token.token_id.clone()).collect()
+        };
+
+        let page = contract.nft_tokens_page(None, Some(2));
+        assert_eq!(ids(&page), vec!["1", "3"]);
+        assert_eq!(page.next_cursor, Some(U64(3)));
+        let page = contract.nft_tokens_page(page.next_cursor, Some(2));
+        assert_eq!(ids(&page), vec!["4", "5"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = contract.nft_tokens_for_owner_page(accounts(1), Some(U64(1)), Some(10));
+        assert_eq!(ids(&page), vec!["3", "4", "5"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = contract.nft_tokens_for_variant_page("e".to_string(), None, None);
+        assert_eq!(ids(&page), vec!["3"]);
+        let page = contract.nft_tokens_for_variant_page("c".to_string(), None, None);
+        assert!(page.tokens.is_empty());
+
+        let tokens = contract.nft_tokens(None, None);
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(contract.nft_total_supply(), U128(4));
+    }
+
+    #[test]
+    fn test_tokens_by_variant() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        // variants cycle a, c, e, b, d, a, c
+        for _ in 0..7 {
+            mint_to(&mut context, &mut contract, accounts(1));
+        }
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_burn("1".to_string());
+        contract.nft_burn("3".to_string());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        assert_eq!(contract.nft_supply_by_variant("a".to_string()), U128(1));
+        assert_eq!(contract.nft_supply_by_variant("c".to_string()), U128(2));
+        assert_eq!(contract.nft_supply_by_variant("z".to_string()), U128(0));
+
+        let tokens = contract.nft_tokens_by_variant("c".to_string(), None, None);
+        let token_ids: Vec<TokenId> = tokens.into_iter().map(|token| token.token_id).collect();
+        assert_eq!(token_ids, vec!["2", "7"]);
+        let tokens = contract.nft_tokens_by_variant("c".to_string(), Some(U128(1)), Some(1));
+        assert_eq!(tokens[0].token_id, "7");
+        let tokens = contract.nft_tokens_by_variant("a".to_string(), None, None);
+        assert_eq!(tokens[0].token_id, "6");
+        assert!(contract.nft_tokens_by_variant("z".to_string(), None, None).is_empty());
+        // every token of "e" was burned
+        assert_eq!(contract.nft_supply_by_variant("e".to_string()), U128(0));
+        assert!(contract.nft_tokens_by_variant("e".to_string(), None, None).is_empty());
+    }
+
+    #[test]
+    fn test_mint_stats() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        for receiver_id in [accounts(1), accounts(1), accounts(2)].iter() {
+            testing_env!(context
+                .storage_usage(env::storage_usage())
+                .attached_deposit(MINT_STORAGE_COST)
+                .predecessor_account_id(accounts(0))
+                .signer_account_id(receiver_id.clone())
+                .build());
+            contract.nft_mint_owner(receiver_id.clone());
+        }
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.nft_transfer(accounts(1), "3".to_string(), None, None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        let stats = contract.mint_stats();
+        assert_eq!(stats.total_minted, 3);
+        assert_eq!(stats.user_minted, 3);
+        assert_eq!(stats.reserve_minted, 0);
+        assert_eq!(stats.variant_minted.get("a"), Some(&1));
+        assert_eq!(stats.variant_minted.get("c"), Some(&1));
+        assert_eq!(stats.variant_minted.get("e"), Some(&1));
+        assert_eq!(stats.remaining_supply, MAX_NFT_MINT - 3);
+        assert_eq!(stats.mint_price, U128(MINT_PRICE));
+        assert_eq!(stats.unique_holders, 1);
+    }
+
+    #[test]
+    fn test_withdraw_proceeds() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        testing_env!(context
+            .attached_deposit(MINT_PRICE)
+            .account_balance(10 * MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint();
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(0)).build());
+        contract.withdraw_proceeds(accounts(3), Some(U128(MINT_PRICE / 2)));
+        let stats = contract.mint_stats();
+        assert_eq!(stats.proceeds_collected, U128(MINT_PRICE));
+        assert_eq!(stats.proceeds_withdrawn, U128(MINT_PRICE / 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount exceeds the proceeds available")]
+    fn test_withdraw_more_than_proceeds() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        testing_env!(context
+            .attached_deposit(MINT_PRICE)
+            .account_balance(10 * MINT_PRICE)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_mint();
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(0)).build());
+        contract.withdraw_proceeds(accounts(3), Some(U128(MINT_PRICE + 1)));
+    }
+
+    #[test]
+    fn test_holder_snapshot_is_frozen() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        for receiver_id in [accounts(1), accounts(1), accounts(2)].iter() {
+            mint_to(&mut context, &mut contract, receiver_id.clone());
+        }
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let snapshot_id = contract.snapshot_holders();
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.nft_transfer(accounts(3), "3".to_string(), None, None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        let holders = contract.holders(None, None);
+        assert_eq!(holders.len(), 2);
+        assert!(holders.contains(&(accounts(1).to_string(), 2)));
+        assert!(holders.contains(&(accounts(3).to_string(), 1)));
+
+        assert_eq!(contract.get_holder_snapshot(snapshot_id).unwrap().holder_count, 2);
+        let snapshot = contract.holders_at_snapshot(snapshot_id, None, None);
+        assert!(snapshot.contains(&(accounts(1).to_string(), 2)));
+        assert!(snapshot.contains(&(accounts(2).to_string(), 1)));
+        assert_eq!(contract.holders_at_snapshot(snapshot_id, Some(U128(1)), Some(5)).len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_from_first_release() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut old = migrate::ContractV1 {
+            tokens: NonFungibleToken::new(
+                StorageKey::NonFungibleToken,
+                accounts(0),
+                Some(StorageKey::TokenMetadata),
+                Some(StorageKey::Enumeration),
+                Some(StorageKey::Approval),
+            ),
+            metadata: LazyOption::new(
+                StorageKey::Metadata,
+                Some(&NFTContractMetadata {
+                    spec: NFT_METADATA_SPEC.to_string(),
+                    name: "Near Hub NFT Comics".to_string(),
+                    symbol: "NHNFTC".to_string(),
+                    icon: None,
+                    base_uri: None,
+                    reference: None,
+                    reference_hash: None,
+                }),
+            ),
+            token_minted: 3,
+            token_minted_users: 3,
+            current_index: 1,
+        };
+        // the first release also minted tokens with ids of the caller's choosing
+        let legacy = [("1", "a"), ("2", "c"), ("3", "e"), ("7", "b"), ("special", "d")];
+        for (token_id, variant) in legacy.iter() {
+            testing_env!(context
+                .storage_usage(env::storage_usage())
+                .attached_deposit(MINT_STORAGE_COST)
+                .predecessor_account_id(accounts(0))
+                .build());
+            let metadata = TokenMetadata {
+                title: Some(format!("HRMS #1{} Whitelist NFTs", variant)),
+                media: Some(format!("{}/{}", DEFAULT_BASE_URI, NFT_IMAGES[0])),
+                ..sample_token_metadata()
+            };
+            old.tokens.mint(token_id.to_string(), accounts(1), Some(metadata));
+        }
+        env::state_write(&old);
+
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.legacy_tokens_to_backfill(), 5);
+        assert_eq!(contract.nft_circulating_supply(), 5);
+        assert!(!contract.backfill_legacy_tokens(3));
+        // tokens "7" and "special" aren't indexed yet
+        let page = contract.nft_tokens_for_owner_page(accounts(1), None, None);
+        assert_eq!(page.tokens.len(), 3);
+        assert!(contract.backfill_legacy_tokens(3));
+        let page = contract.nft_tokens_for_owner_page(accounts(1), None, None);
+        assert_eq!(page.tokens.len(), 5);
+        assert_eq!(contract.legacy_tokens_to_backfill(), 0);
+        assert_eq!(contract.nft_metadata().base_uri.unwrap(), DEFAULT_BASE_URI);
+        let legacy_token = contract.internal_token(&"special".to_string());
+        assert_eq!(legacy_token.metadata.unwrap().media.unwrap(), NFT_IMAGES[0]);
+
+        // "7" and "special" took sequence numbers 4 and 5, and id "7" is skipped
+        let token = mint_to(&mut context, &mut contract, accounts(2));
+        assert_eq!(token.token_id, "6");
+        let token = mint_to(&mut context, &mut contract, accounts(2));
+        assert_eq!(token.token_id, "8");
+
+        let stats = contract.mint_stats();
+        assert_eq!(stats.total_minted, 5);
+        assert_eq!(stats.circulating_supply, 7);
+        assert_eq!(stats.unique_holders, 2);
+        assert_eq!(contract.nft_circulating_supply(), 7);
+        assert_eq!(contract.nft_supply_by_variant("c".to_string()), U128(1));
+        assert_eq!(contract.nft_tokens_page(None, None).tokens.len(), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Numeric token ids are reserved for the token sequence")]
+    fn test_mint_rejects_numeric_token_id() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.mint("2".to_string(), accounts(1), Some(sample_token_metadata()));
+    }
+
+    #[test]
+    fn test_token_id_template() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_token_id_template("hrms1-{variant}-{edition}".to_string());
+
+        // variants cycle a, c, e, b, d, a
+        let mut token_ids = vec![];
+        for _ in 0..6 {
+            token_ids.push(mint_to(&mut context, &mut contract, accounts(1)).token_id);
+        }
+        assert_eq!(token_ids[0], "hrms1-a-1");
+        assert_eq!(token_ids[1], "hrms1-c-1");
+        assert_eq!(token_ids[5], "hrms1-a-2");
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .account_balance(env::account_balance())
+            .is_view(true)
+            .attached_deposit(0)
+            .build());
+        assert_eq!(contract.nft_token_by_number(U64(6)).unwrap().token_id, "hrms1-a-2");
+        assert_eq!(contract.nft_token_number("hrms1-c-1".to_string()), Some(U64(2)));
+        assert!(contract.nft_token_by_number(U64(7)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token id template must contain")]
+    fn test_token_id_template_must_be_unique() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_token_id_template("hrms1-{edition}".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token id template must start with a literal prefix")]
+    fn test_token_id_template_needs_prefix() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_token_id_template("{variant}{edition}".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token id template can't change once tokens are minted")]
+    fn test_token_id_template_fixed_after_mint() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        mint_to(&mut context, &mut contract, accounts(1));
+        contract.set_token_id_template("hrms1-{sequence}".to_string());
+    }
+
+    #[test]
+    fn test_edition_numbering() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        // variants cycle a, c, e, b, d, a
+        let mut tokens = vec![];
+        for _ in 0..6 {
+            tokens.push(mint_to(&mut context, &mut contract, accounts(1)));
+        }
+        let metadata = tokens[5].metadata.clone().unwrap();
+        assert_eq!(metadata.title.unwrap(), "HRMS #1a — 2/100");
+        let extra: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(extra["variant"], "a");
+        assert_eq!(extra["edition"], 2);
+        assert_eq!(extra["edition_size"], 100);
+        assert_eq!(tokens[1].metadata.clone().unwrap().title.unwrap(), "HRMS #1c — 1/100");
+    }
+
+    #[test]
+    fn test_variant_traits_in_extra() {
+        #[derive(Deserialize)]
+        #[serde(crate = "near_sdk::serde")]
+        struct Extra {
+            attributes: Vec<TokenAttribute>,
+            edition: u64,
+        }
+
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let attributes = vec![
+            TokenAttribute {
+                trait_type: "Background".to_string(),
+                value: "Teal".to_string(),
+                display_type: None,
+            },
+            TokenAttribute {
+                trait_type: "Issue".to_string(),
+                value: "1".to_string(),
+                display_type: Some("number".to_string()),
+            },
+        ];
+        contract.set_variant_config(
+            "a".to_string(),
+            VariantConfig {
+                media: None,
+                attributes: attributes.clone(),
+                reference: Some("https://example.com/hrms1-a.json".to_string()),
+                reference_hash: Some(vec![7u8; 32].into()),
+                soulbound: false,
+            },
+        );
+
+        let metadata = mint_to(&mut context, &mut contract, accounts(1)).metadata.unwrap();
+        let extra: Extra = near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(extra.attributes, attributes);
+        assert_eq!(extra.edition, 1);
+        assert_eq!(metadata.reference.unwrap(), "https://example.com/hrms1-a.json");
+        assert_eq!(metadata.reference_hash.unwrap().0, vec![7u8; 32]);
+
+        // variants without a config get no traits
+        let metadata = mint_to(&mut context, &mut contract, accounts(1)).metadata.unwrap();
+        let extra: Extra = near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert!(extra.attributes.is_empty());
+        assert!(metadata.reference.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Reference and reference hash must be set together")]
+    fn test_variant_reference_requires_hash() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_variant_config(
+            "b".to_string(),
+            VariantConfig {
+                media: None,
+                attributes: vec![],
+                reference: Some("https://example.com/hrms1-b.json".to_string()),
+                reference_hash: None,
+                soulbound: false,
+            },
+        );
+    }
+
+    #[test]
+    fn test_set_base_uri() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_base_uri("https://ipfs.io/ipfs/".to_string());
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"event\":\"contract_metadata_update\""));
+        contract.set_variant_config(
+            "a".to_string(),
+            VariantConfig { media: Some("bafyguest/a.png".to_string()), ..Default::default() },
+        );
+
+        let token = mint_to(&mut context, &mut contract, accounts(1));
+        assert_eq!(token.metadata.unwrap().media.unwrap(), "bafyguest/a.png");
+        assert_eq!(contract.nft_metadata().base_uri.unwrap(), "https://ipfs.io/ipfs");
+    }
+
+    #[test]
+    fn test_new_defaults_base_uri() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new(
+            accounts(0),
+            NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: "Comics".to_string(),
+                symbol: "COMICS".to_string(),
+                icon: None,
+                base_uri: None,
+                reference: None,
+                reference_hash: None,
+            },
+        );
+        assert_eq!(contract.nft_metadata().base_uri.unwrap(), DEFAULT_BASE_URI);
+
+        let token = mint_to(&mut context, &mut contract, accounts(1));
+        assert_eq!(token.metadata.unwrap().media.unwrap(), NFT_IMAGES[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Base URI can't be empty")]
+    fn test_set_base_uri_rejects_empty() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_base_uri("/".to_string());
+    }
+
+    #[test]
+    fn test_update_contract_metadata() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let metadata = contract.update_contract_metadata(ContractMetadataUpdate {
+            name: Some("HRMS #1 Whitelist".to_string()),
+            reference: Some("https://example.com/hrms1.json".to_string()),
+            reference_hash: Some(vec![1u8; 32].into()),
+            ..Default::default()
+        });
+        assert_eq!(metadata.name, "HRMS #1 Whitelist");
+        assert_eq!(metadata.symbol, "NHNFTC");
+        assert_eq!(contract.nft_metadata().reference.unwrap(), "https://example.com/hrms1.json");
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs[0].contains("\"memo\":\"Updated name, reference\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "Hash has to be 32 bytes")]
+    fn test_update_contract_metadata_validates() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.update_contract_metadata(ContractMetadataUpdate {
+            reference: Some("https://example.com/hrms1.json".to_string()),
+            reference_hash: Some(vec![1u8; 4].into()),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_update_token_metadata() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.add_metadata_updater(accounts(2));
+
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(42)
+            .build());
+        let metadata = contract.nft_update_token_metadata(
+            token_id.clone(),
+            TokenMetadataUpdate {
+                description: Some("Chapter two".to_string()),
+                extra: Some("{\"chapter\":2}".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(metadata.description.unwrap(), "Chapter two");
+        assert_eq!(metadata.updated_at.unwrap(), "42");
+        let extra: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(extra["chapter"], 2);
+        assert_eq!(extra["edition"], 1);
+
+        contract.lock_token_metadata(token_id.clone());
+        assert!(contract.is_token_metadata_locked(token_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Extra key edition is reserved")]
+    fn test_update_token_metadata_reserved_extra() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+        contract.nft_update_token_metadata(
+            token_id,
+            TokenMetadataUpdate {
+                extra: Some("{\"edition\":7}".to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Extra key tier is reserved")]
+    fn test_update_token_metadata_reserved_tier() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+        contract.nft_update_token_metadata(
+            token_id,
+            TokenMetadataUpdate {
+                extra: Some("{\"tier\":\"Gold\"}".to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Token metadata is locked")]
+    fn test_update_locked_token_metadata() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+        contract.lock_all_token_metadata();
+        contract.nft_update_token_metadata(
+            token_id,
+            TokenMetadataUpdate { description: Some("Retcon".to_string()), ..Default::default() },
+        );
+    }
+
+    #[test]
+    fn test_freeze_metadata() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+        assert!(!contract.is_metadata_frozen());
+        contract.freeze_metadata();
+        assert!(contract.is_metadata_frozen());
+        assert!(contract.is_token_metadata_locked(token_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Metadata is frozen")]
+    fn test_frozen_metadata_rejects_contract_metadata() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.freeze_metadata();
+        contract.update_contract_metadata(ContractMetadataUpdate {
+            name: Some("Renamed".to_string()),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Metadata is frozen")]
+    fn test_frozen_metadata_rejects_variant_config() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.freeze_metadata();
+        contract.set_variant_config("a".to_string(), VariantConfig::default());
+    }
+
+    #[test]
+    fn test_frozen_metadata_keeps_transfer_rules() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.freeze_metadata();
+        contract.set_transfer_lock(Some(U64(1_000)));
+        contract.set_validity_window(None, Some(U64(2_000)), true);
+        let config = contract.internal_config();
+        assert_eq!(config.transfer_lock_ns, Some(U64(1_000)));
+        assert_eq!(config.expires_at, Some(U64(2_000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Metadata is frozen")]
+    fn test_frozen_metadata_rejects_base_uri() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.freeze_metadata();
+        contract.set_base_uri("https://ipfs.io/ipfs".to_string());
+    }
+
+    /// Decodes the `data:image/svg+xml` URI of a token's media.
+    fn decode_svg_data_uri(media: &str) -> String {
+        let encoded = media.strip_prefix("data:image/svg+xml,").expect("Not an SVG data URI");
+        let mut bytes = vec![];
+        let mut chars = encoded.bytes();
+        while let Some(byte) = chars.next() {
+            if byte == b'%' {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                let hex = std::str::from_utf8(&hex).unwrap();
+                bytes.push(u8::from_str_radix(hex, 16).expect("Invalid percent-encoding"));
+            } else {
+                assert!(!b"<>#\"".contains(&byte), "Unencoded {} in data URI", byte as char);
+                bytes.push(byte);
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn assert_valid_xml_text(text: &str) {
+        assert!(!text.contains('<') && !text.contains('>'), "Unescaped markup in {}", text);
+        for (index, _) in text.match_indices('&') {
+            let entity = &text[index..];
+            assert!(
+                ["&amp;", "&lt;", "&gt;", "&apos;", "&quot;"].iter().any(|e| entity.starts_with(e)),
+                "Unescaped & in {}",
+                text
+            );
+        }
+    }
+
+    /// Checks that `svg` is a well-formed XML document with a single `svg` root element.
+    fn assert_well_formed_svg(svg: &str) {
+        let mut open: Vec<&str> = vec![];
+        let mut root_seen = false;
+        let mut rest = svg;
+        while let Some(start) = rest.find('<') {
+            let text = &rest[..start];
+            assert!(!open.is_empty() || text.trim().is_empty(), "Text outside the root element");
+            assert_valid_xml_text(text);
+            let end = start + rest[start..].find('>').expect("Unterminated tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name.trim()), "Mismatched closing tag");
+                continue;
+            }
+            assert!(!open.is_empty() || !root_seen, "More than one root element");
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            let name = &tag[..name_end];
+            assert!(!name.is_empty() && name.chars().all(char::is_alphanumeric), "Bad tag name");
+            if !root_seen {
+                assert_eq!(name, "svg", "Root element is not svg");
+                assert!(tag.contains("xmlns='http://www.w3.org/2000/svg'"), "Missing namespace");
+                root_seen = true;
+            }
+
+            let mut attributes = tag[name_end..].trim();
+            let mut names = vec![];
+            while !attributes.is_empty() {
+                let eq = attributes.find('=').expect("Attribute without value");
+                let attribute = attributes[..eq].trim();
+                assert!(!names.contains(&attribute), "Duplicate attribute {}", attribute);
+                names.push(attribute);
+                let value = &attributes[eq + 1..];
+                let quote = value.chars().next().filter(|q| *q == '\'' || *q == '"');
+                let quote = quote.expect("Unquoted attribute value");
+                let close = 1 + value[1..].find(quote).expect("Unterminated attribute value");
+                assert_valid_xml_text(&value[1..close]);
+                attributes = value[close + 1..].trim_start();
+            }
+            if !self_closing {
+                open.push(name);
+            }
+        }
+        assert!(root_seen && open.is_empty(), "Unclosed elements {:?}", open);
+        assert!(rest.trim().is_empty(), "Text after the root element");
+    }
+
+    #[test]
+    fn test_on_chain_media() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        mint_to(&mut context, &mut contract, accounts(0));
+        mint_to(&mut context, &mut contract, accounts(0));
+        let stored_media = contract.nft_token_by_number(U64(1)).unwrap().metadata.unwrap().media;
+        assert_eq!(stored_media, sample_token_metadata().media);
+
+        contract.set_on_chain_media(true);
+        assert!(contract.get_collection_config().on_chain_media);
+        let media = contract.nft_token_by_number(U64(1)).unwrap().metadata.unwrap().media.unwrap();
+        let svg = decode_svg_data_uri(&media);
+        assert_well_formed_svg(&svg);
+        assert!(svg.contains("#00b7b2"));
+        assert!(svg.contains(">HRMS #1a<") && svg.contains(">1/100<"));
+
+        let second = contract.nft_token_by_number(U64(2)).unwrap().metadata.unwrap().media;
+        let second = decode_svg_data_uri(&second.unwrap());
+        assert_well_formed_svg(&second);
+        assert!(second.contains("#7b3fe4") && second.contains(">1/100<"));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None);
+        let token = contract.nft_token("1".to_string()).unwrap();
+        assert_eq!(token.metadata.unwrap().media.unwrap(), media);
+    }
+
+    #[test]
+    fn test_render_token_svg_escapes_text() {
+        let svg = render::render_token_svg(Some("<&'>"), "\"7\"");
+        assert_well_formed_svg(&svg);
+        assert!(svg.contains("#444444"));
+        assert_well_formed_svg(&decode_svg_data_uri(&render::svg_data_uri(&svg)));
+        assert_well_formed_svg(&render::render_token_svg(None, "12"));
+    }
+
+    fn sample_evolution_tiers() -> Vec<EvolutionTier> {
+        vec![
+            EvolutionTier { min_holding_ns: U64(1_000), name: "Bronze".to_string(), media: None },
+            EvolutionTier {
+                min_holding_ns: U64(5_000),
+                name: "Gold".to_string(),
+                media: Some("bafygold/gold.png".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_evolution_tiers() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_evolution_tiers(sample_evolution_tiers());
+
+        context.block_timestamp(100);
+        mint_to(&mut context, &mut contract, accounts(0));
+        assert_eq!(contract.token_held_since("1".to_string()), Some(U64(100)));
+        assert_eq!(contract.nft_token_tier("1".to_string()), None);
+
+        testing_env!(context.block_timestamp(2_000).build());
+        let metadata = contract.nft_token_by_number(U64(1)).unwrap().metadata.unwrap();
+        assert_eq!(metadata.title.unwrap(), "HRMS #1a — 1/100 · Bronze");
+        assert_eq!(metadata.media, sample_token_metadata().media);
+        let extra: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(extra["tier"], "Bronze");
+
+        testing_env!(context.block_timestamp(6_000).attached_deposit(1).build());
+        let metadata = contract.nft_token_by_number(U64(1)).unwrap().metadata.unwrap();
+        assert_eq!(metadata.title.unwrap(), "HRMS #1a — 1/100 · Gold");
+        assert_eq!(metadata.media.unwrap(), "bafygold/gold.png");
+
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None);
+        assert_eq!(contract.token_held_since("1".to_string()), Some(U64(6_000)));
+        testing_env!(context.block_timestamp(6_500).build());
+        assert_eq!(contract.nft_token_tier("1".to_string()), None);
+        let metadata = contract.nft_token("1".to_string()).unwrap().metadata.unwrap();
+        assert_eq!(metadata.title, sample_token_metadata().title);
+        assert_eq!(metadata.media, sample_token_metadata().media);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tiers must be ordered by increasing holding time")]
+    fn test_evolution_tiers_must_be_ordered() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut tiers = sample_evolution_tiers();
+        tiers.reverse();
+        contract.set_evolution_tiers(tiers);
+    }
+
+    #[test]
+    fn test_reverted_transfer_call_keeps_held_since() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        context.block_timestamp(100);
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+
+        testing_env!(context
+            .block_timestamp(2_000)
+            .storage_usage(env::storage_usage())
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.nft_transfer_call(accounts(2), token_id.clone(), None, None, "".to_string());
+        assert_eq!(contract.token_held_since(token_id.clone()), Some(U64(2_000)));
+
+        testing_env_with_promise_results(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            PromiseResult::Failed,
+        );
+        assert!(!contract.nft_resolve_transfer(
+            accounts(1).into(),
+            accounts(2).into(),
+            token_id.clone(),
+            None
+        ));
+        assert_eq!(contract.tokens.owner_by_id.get(&token_id).unwrap(), accounts(1).to_string());
+        assert_eq!(contract.token_held_since(token_id), Some(U64(100)));
+    }
+
+    #[test]
+    fn test_failed_redeem_keeps_held_since() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_pfp_contract(accounts(3));
+        context.block_timestamp(100);
+        let token_id = mint_to(&mut context, &mut contract, accounts(1)).token_id;
+
+        testing_env!(context
+            .block_timestamp(2_000)
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.redeem(token_id.clone(), accounts(1));
+        assert_eq!(contract.token_held_since(token_id.clone()), Some(U64(2_000)));
+
+        testing_env_with_promise_results(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            PromiseResult::Failed,
+        );
+        assert!(!contract.resolve_redeem(
+            token_id.clone(),
+            accounts(1).into(),
+            accounts(1).into(),
+            accounts(3).into(),
+        ));
+        assert_eq!(contract.tokens.owner_by_id.get(&token_id).unwrap(), accounts(1).to_string());
+        assert_eq!(contract.token_held_since(token_id), Some(U64(100)));
+    }
+
+    #[test]
+    fn test_validity_window() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_validity_window(Some(U64(1_000)), Some(U64(5_000)), false);
+
+        mint_to(&mut context, &mut contract, accounts(0));
+        let metadata = contract.nft_token_by_number(U64(1)).unwrap().metadata.unwrap();
+        assert_eq!(metadata.starts_at.unwrap(), "1000");
+        assert_eq!(metadata.expires_at.unwrap(), "5000");
+        assert!(!contract.is_valid("1".to_string()));
+
+        testing_env!(context.block_timestamp(2_000).build());
+        assert!(contract.is_valid("1".to_string()));
+        testing_env!(context.block_timestamp(5_000).attached_deposit(1).build());
+        assert!(!contract.is_valid("1".to_string()));
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token has expired")]
+    fn test_transfer_expired_token() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_validity_window(None, Some(U64(5_000)), true);
+
+        mint_to(&mut context, &mut contract, accounts(0));
+
+        testing_env!(context.block_timestamp(6_000).attached_deposit(1).build());
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is not valid yet")]
+    fn test_mark_redeemed_before_validity() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_validity_window(Some(U64(1_000)), None, false);
+        contract.add_redeemer(accounts(2));
+
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.mark_redeemed("1".to_string());
+    }
+
+    fn soulbound_contract(context: &mut VMContextBuilder) -> Contract {
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let soulbound = VariantConfig { soulbound: true, ..Default::default() };
+        contract.set_variant_config("a".to_string(), soulbound);
+
+        mint_to(context, &mut contract, accounts(1));
+        mint_to(context, &mut contract, accounts(1));
+        contract
+    }
+
+    #[test]
+    fn test_soulbound_token_can_be_burned() {
+        let mut context = get_context(accounts(0));
+        let mut contract = soulbound_contract(&mut context);
+        assert!(!contract.is_transferable("1".to_string()));
+        assert!(contract.is_transferable("2".to_string()));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_transfer(accounts(2), "2".to_string(), None, None);
+        contract.nft_burn("1".to_string());
+        assert_eq!(contract.nft_circulating_supply(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is soulbound")]
+    fn test_soulbound_token_transfer() {
+        let mut context = get_context(accounts(0));
+        let mut contract = soulbound_contract(&mut context);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_transfer(accounts(2), "1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is soulbound")]
+    fn test_soulbound_token_approve() {
+        let mut context = get_context(accounts(0));
+        let mut contract = soulbound_contract(&mut context);
+
+        testing_env!(context
+            .attached_deposit(150000000000000000000)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_approve("1".to_string(), accounts(2), None);
+    }
+
+    #[test]
+    fn test_set_token_transferable() {
+        let mut context = get_context(accounts(0));
+        let mut contract = soulbound_contract(&mut context);
+        contract.set_token_transferable("1".to_string(), true);
+        contract.set_token_transferable("2".to_string(), false);
+        assert!(contract.is_transferable("1".to_string()));
+        assert!(!contract.is_transferable("2".to_string()));
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_transfer(accounts(2), "1".to_string(), None, None);
+    }
+
+    #[test]
+    fn test_transfer_lock() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_transfer_lock(Some(U64(1_000)));
+
+        context.block_timestamp(500);
+        mint_to(&mut context, &mut contract, accounts(0));
+        assert_eq!(contract.transfer_unlocks_at("1".to_string()), Some(U64(1_500)));
+        let metadata = contract.nft_token_by_number(U64(1)).unwrap().metadata.unwrap();
+        let extra: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&metadata.extra.unwrap()).unwrap();
+        assert_eq!(extra["transfer_unlocks_at"], "1500");
+
+        contract.set_transfer_lock(None);
+        contract.nft_mint_owner(accounts(0));
+        assert_eq!(contract.transfer_unlocks_at("2".to_string()), None);
+
+        testing_env!(context.block_timestamp(1_500).attached_deposit(1).build());
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is locked until 1500")]
+    fn test_transfer_locked_token() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        contract.set_transfer_lock(Some(U64(1_000)));
+
+        context.block_timestamp(500);
+        mint_to(&mut context, &mut contract, accounts(0));
+
+        testing_env!(context.block_timestamp(1_499).attached_deposit(1).build());
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None);
+    }
+
+    const LISTING_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
+    const LISTING_PRICE: Balance = 1_000_000_000_000_000_000_000_000;
+
+    fn listed_contract(context: &mut VMContextBuilder) -> Contract {
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+        let mut royalty = HashMap::new();
+        royalty.insert(accounts(3).to_string(), 1_000);
+        contract.set_royalty(royalty);
+
+        mint_to(context, &mut contract, accounts(1));
+        mint_to(context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(LISTING_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.list("1".to_string(), U128(LISTING_PRICE));
+        contract.list("2".to_string(), U128(LISTING_PRICE));
+        contract
+    }
+
+    #[test]
+    fn test_list_and_buy() {
+        let mut context = get_context(accounts(0));
+        let mut contract = listed_contract(&mut context);
+        assert_eq!(contract.get_listing_count(), 2);
+        assert_eq!(contract.get_listings(Some(U128(1)), Some(10))[0].token_id, "2");
+        let seller_listings = contract.get_listings_by_seller(accounts(1), None, None);
+        assert_eq!(seller_listings.len(), 2);
+        assert_eq!(seller_listings[0].price, U128(LISTING_PRICE));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(LISTING_PRICE)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.buy("1".to_string());
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains("\"event\":\"listing_sale\"")));
+        assert_eq!(contract.get_listing("1".to_string()), None);
+        assert_eq!(contract.get_listing_count(), 1);
+        assert_eq!(contract.get_listings_by_seller(accounts(1), None, None).len(), 1);
+        let token = contract.nft_token_by_number(U64(1)).unwrap();
+        assert_eq!(token.owner_id, accounts(2).to_string());
+    }
+
+    #[test]
+    fn test_listing_dropped_on_transfer() {
+        let mut context = get_context(accounts(0));
+        let mut contract = listed_contract(&mut context);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_transfer(accounts(2), "1".to_string(), None, None);
+        contract.delist("2".to_string());
+        assert_eq!(contract.get_listing_count(), 0);
+        assert!(contract.get_listings_by_seller(accounts(1), None, None).is_empty());
+    }
+
+    #[test]
+    fn test_transfer_refunds_listing_storage_once() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        mint_to(&mut context, &mut contract, accounts(1));
+        mint_to(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(LISTING_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.storage_deposit(None, None);
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let initial_storage_usage = env::storage_usage();
+        contract.list("1".to_string(), U128(LISTING_PRICE));
+        let listing_bytes = env::storage_usage() - initial_storage_usage;
+        let available = contract.storage_balance_of(accounts(1)).unwrap().available.0;
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let initial_storage_usage = env::storage_usage() - listing_bytes;
+        contract.nft_transfer(accounts(2), "1".to_string(), None, None);
+        // the listing comes back once, the receiver's token set is drawn from the balance
+        let storage_used = env::storage_usage() - initial_storage_usage;
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(
+            balance.available.0,
+            available + Balance::from(listing_bytes) * env::storage_byte_cost()
+                - Balance::from(storage_used) * env::storage_byte_cost()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit is less than the price")]
+    fn test_buy_underpaid() {
+        let mut context = get_context(accounts(0));
+        let mut contract = listed_contract(&mut context);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(LISTING_PRICE - 1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.buy("1".to_string());
+    }
+
+    fn auction_contract(context: &mut VMContextBuilder) -> Contract {
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0).into());
+
+        mint_to(context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(LISTING_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.start_auction("1".to_string(), U128(LISTING_PRICE), U64(10_000));
+        contract
+    }
+
+    fn place_bid(
+        context: &mut VMContextBuilder,
+        contract: &mut Contract,
+        bidder: usize,
+        amount: Balance,
+    ) {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(amount)
+            .predecessor_account_id(accounts(bidder))
+            .block_timestamp(100)
+            .build());
+        contract.bid("1".to_string());
+    }
+
+    #[test]
+    fn test_auction_settles_to_highest_bidder() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        let auction = contract.get_auction("1".to_string()).unwrap();
+        assert_eq!(auction.seller_id, accounts(1).to_string());
+        assert!(contract.tokens.approvals_by_id.as_ref().unwrap().get(&"1".to_string()).is_some());
+
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+        place_bid(&mut context, &mut contract, 3, 2 * LISTING_PRICE);
+        let highest_bid = contract.get_auction("1".to_string()).unwrap().highest_bid.unwrap();
+        assert_eq!(highest_bid.bidder_id, accounts(3).to_string());
+        assert_eq!(contract.get_auctions(None, None).len(), 1);
+
+        testing_env!(context.block_timestamp(10_000).attached_deposit(0).build());
+        assert!(contract.settle_auction("1".to_string()));
+        assert_eq!(contract.get_auction_count(), 0);
+        let token = contract.nft_token_by_number(U64(1)).unwrap();
+        assert_eq!(token.owner_id, accounts(3).to_string());
+        assert!(token.approved_account_ids.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_auction_without_bids_revokes_approval() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+
+        testing_env!(context.block_timestamp(10_000).attached_deposit(0).build());
+        assert!(!contract.settle_auction("1".to_string()));
+        let token = contract.nft_token_by_number(U64(1)).unwrap();
+        assert_eq!(token.owner_id, accounts(1).to_string());
+        assert!(token.approved_account_ids.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_auction_without_bids_dropped_on_transfer() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_transfer(accounts(4), "1".to_string(), None, None);
+        assert_eq!(contract.get_auction("1".to_string()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is on auction with bids")]
+    fn test_auction_with_bids_blocks_transfer() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_transfer(accounts(4), "1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is on auction with bids")]
+    fn test_auction_with_bids_blocks_burn() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_burn("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is on auction with bids")]
+    fn test_auction_with_bids_blocks_revoke() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.nft_revoke("1".to_string(), accounts(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is on auction")]
+    fn test_list_token_on_auction() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.list("1".to_string(), U128(LISTING_PRICE));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is listed, delist it first")]
+    fn test_auction_listed_token() {
+        let mut context = get_context(accounts(0));
+        let mut contract = listed_contract(&mut context);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.start_auction("1".to_string(), U128(LISTING_PRICE), U64(10_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bid must be higher than the current bid")]
+    fn test_auction_rejects_lower_bid() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, 2 * LISTING_PRICE);
+        place_bid(&mut context, &mut contract, 3, LISTING_PRICE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is on auction")]
+    fn test_auction_rejects_reapproval() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+
+        testing_env!(context
+            .attached_deposit(LISTING_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.nft_approve("1".to_string(), accounts(0), None);
+    }
+
+    #[test]
+    fn test_auction_settles_after_reapproval() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+        // the contract's approval id changed since the auction started
+        contract.internal_approve(&"1".to_string(), &accounts(0).to_string());
+
+        testing_env!(context.block_timestamp(10_000).attached_deposit(0).build());
+        assert!(contract.settle_auction("1".to_string()));
+        let token = contract.nft_token_by_number(U64(1)).unwrap();
+        assert_eq!(token.owner_id, accounts(2).to_string());
+    }
+
+    #[test]
+    fn test_auction_refunds_bid_on_soulbound_token() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+
+        testing_env!(context
+            .block_timestamp(10_000)
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .build());
+        contract.set_token_transferable("1".to_string(), false);
+        assert!(!contract.settle_auction("1".to_string()));
+        assert_eq!(contract.get_auction_count(), 0);
+        let token = contract.nft_token_by_number(U64(1)).unwrap();
+        assert_eq!(token.owner_id, accounts(1).to_string());
+        assert!(token.approved_account_ids.unwrap().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't cancel an auction with bids")]
+    fn test_cancel_auction_with_bids() {
+        let mut context = get_context(accounts(0));
+        let mut contract = auction_contract(&mut context);
+        place_bid(&mut context, &mut contract, 2, LISTING_PRICE);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.cancel_auction("1".to_string());
+    }
 }
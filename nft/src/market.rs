@@ -0,0 +1,163 @@
+use crate::events::log_whitelist_event;
+use crate::*;
+use near_sdk::{assert_one_yocto, Balance};
+
+/// Fixed-price offer to sell a token through `buy`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Listing {
+    pub token_id: TokenId,
+    /// Owner of the token when it was listed. The listing is dropped once the token moves.
+    pub seller_id: AccountId,
+    /// Price in yoctoNEAR, royalties included.
+    pub price: U128,
+    pub listed_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Lists `token_id` for sale at `price` yoctoNEAR, replacing its previous listing. Only the
+    /// token owner can list it, and pays for the listing's storage.
+    #[payable]
+    pub fn list(&mut self, token_id: TokenId, price: U128) -> Listing {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), owner_id, "Only the token owner can list it");
+        assert!(price.0 > 0, "Price must be positive");
+        assert!(self.auctions.get(&token_id).is_none(), "Token is on auction");
+        self.assert_transferable(&token_id);
+
+        let initial_storage_usage = env::storage_usage();
+        let listing = Listing {
+            token_id: token_id.clone(),
+            seller_id: owner_id.clone(),
+            price,
+            listed_at: env::block_timestamp().into(),
+        };
+        self.listings.insert(&token_id, &listing);
+        let mut seller_listings = self.listings_by_seller.get(&owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::ListingsBySellerInner { account_id: owner_id.clone() })
+        });
+        seller_listings.insert(&token_id);
+        self.listings_by_seller.insert(&owner_id, &seller_listings);
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        self.internal_charge_storage(&owner_id, storage_used);
+
+        log_whitelist_event("listing_create", json!([listing]));
+        listing
+    }
+
+    /// Withdraws the listing of `token_id`, refunding its storage. Only the seller can delist.
+    #[payable]
+    pub fn delist(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let listing = self.listings.get(&token_id).expect("Token is not listed");
+        assert_eq!(env::predecessor_account_id(), listing.seller_id, "Only the seller can delist");
+        self.internal_remove_listing(&token_id);
+    }
+
+    /// Buys the listed `token_id`. The attached deposit has to cover the listing price, the
+    /// excess being refunded. The price is paid out to the royalty receivers of the token and
+    /// the rest to the seller.
+    #[payable]
+    pub fn buy(&mut self, token_id: TokenId) {
+        let listing = self.listings.get(&token_id).expect("Token is not listed");
+        let buyer_id = env::predecessor_account_id();
+        assert_ne!(buyer_id, listing.seller_id, "The seller can't buy their own token");
+        let price: Balance = listing.price.into();
+        let deposit = env::attached_deposit();
+        assert!(deposit >= price, "Attached deposit is less than the price of {}", price);
+        self.assert_transferable(&token_id);
+
+        self.internal_remove_listing(&token_id);
+        let mut initial_storage_usage = env::storage_usage();
+        let (_, approved_account_ids) =
+            self.tokens.internal_transfer(&listing.seller_id, &buyer_id, &token_id, None, None);
+        if let Some(approved_account_ids) = approved_account_ids {
+            initial_storage_usage -=
+                self.internal_refund_approvals(&listing.seller_id, &approved_account_ids);
+        }
+        self.internal_on_transfer(&token_id, &listing.seller_id, &buyer_id);
+        self.internal_draw_storage(&buyer_id, initial_storage_usage);
+
+        self.internal_pay_sale(&token_id, &listing.seller_id, price);
+        if deposit > price {
+            Promise::new(buyer_id.clone()).transfer(deposit - price);
+        }
+        log_whitelist_event(
+            "listing_sale",
+            json!([{
+                "token_id": token_id,
+                "seller_id": listing.seller_id,
+                "buyer_id": buyer_id,
+                "price": listing.price,
+            }]),
+        );
+    }
+
+    pub fn get_listing(&self, token_id: TokenId) -> Option<Listing> {
+        self.listings.get(&token_id)
+    }
+
+    pub fn get_listing_count(&self) -> u64 {
+        self.listings.len()
+    }
+
+    /// Lists the current listings by offset. Delisting moves the last listing into the freed
+    /// slot, so the order isn't stable.
+    pub fn get_listings(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Listing> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let listings = self.listings.values_as_vector();
+        (start_index as u64..listings.len()).take(limit).filter_map(|i| listings.get(i)).collect()
+    }
+
+    /// Lists the listings of `seller_id` by offset.
+    pub fn get_listings_by_seller(
+        &self,
+        seller_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Listing> {
+        let seller_listings = match self.listings_by_seller.get(seller_id.as_ref()) {
+            Some(seller_listings) => seller_listings,
+            None => return vec![],
+        };
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let token_ids = seller_listings.as_vector();
+        (start_index as u64..token_ids.len())
+            .take(limit)
+            .filter_map(|index| token_ids.get(index))
+            .filter_map(|token_id| self.listings.get(&token_id))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Drops the listing of `token_id`, if any, and refunds its storage to the seller. Called
+    /// whenever the token moves or is burned, so listings never outlive their seller's token.
+    pub(crate) fn internal_remove_listing(&mut self, token_id: &TokenId) {
+        let initial_storage_usage = env::storage_usage();
+        let listing = match self.listings.remove(token_id) {
+            Some(listing) => listing,
+            None => return,
+        };
+        if let Some(mut seller_listings) = self.listings_by_seller.get(&listing.seller_id) {
+            seller_listings.remove(token_id);
+            if seller_listings.is_empty() {
+                self.listings_by_seller.remove(&listing.seller_id);
+            } else {
+                self.listings_by_seller.insert(&listing.seller_id, &seller_listings);
+            }
+        }
+        let storage_released = initial_storage_usage.saturating_sub(env::storage_usage());
+        self.internal_refund_storage(&listing.seller_id, storage_released);
+        log_whitelist_event(
+            "listing_remove",
+            json!([{ "token_id": token_id, "seller_id": listing.seller_id }]),
+        );
+    }
+}
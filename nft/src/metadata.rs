@@ -0,0 +1,101 @@
+use crate::events::{log_contract_metadata_update, log_whitelist_event};
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+
+/// Changes to the contract metadata, see `update_contract_metadata`. Fields left out are kept.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractMetadataUpdate {
+    pub name: Option<String>,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    /// Must be given along with `reference_hash`.
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Updates the contract metadata and logs a `contract_metadata_update` event listing the
+    /// fields that changed. Not available once the metadata is frozen.
+    pub fn update_contract_metadata(
+        &mut self,
+        update: ContractMetadataUpdate,
+    ) -> NFTContractMetadata {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        let mut metadata = self.internal_metadata();
+        let mut fields = vec![];
+        if let Some(name) = update.name {
+            assert!(!name.trim().is_empty(), "Name can't be empty");
+            metadata.name = name;
+            fields.push("name");
+        }
+        if let Some(icon) = update.icon {
+            metadata.icon = Some(icon);
+            fields.push("icon");
+        }
+        if let Some(base_uri) = update.base_uri {
+            metadata.base_uri = Some(normalize_base_uri(&base_uri));
+            fields.push("base_uri");
+        }
+        if update.reference.is_some() || update.reference_hash.is_some() {
+            metadata.reference = update.reference;
+            metadata.reference_hash = update.reference_hash;
+            fields.push("reference");
+        }
+        assert!(!fields.is_empty(), "Nothing to update");
+        metadata.assert_valid();
+
+        self.metadata.set(&metadata);
+        log_contract_metadata_update(&metadata, &fields.join(", "));
+        metadata
+    }
+
+    /// Points relative token media to another gateway, e.g. `https://ipfs.io/ipfs`. Token media
+    /// is stored relative to it, so it can't be unset.
+    pub fn set_base_uri(&mut self, base_uri: String) {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        let mut metadata = self.internal_metadata();
+        metadata.base_uri = Some(normalize_base_uri(&base_uri));
+        self.metadata.set(&metadata);
+        log_contract_metadata_update(&metadata, "base_uri");
+    }
+
+    /// Permanently freezes the contract metadata, the collection config and the metadata of
+    /// every token. There is no way back. The transfer lock and the validity window only apply
+    /// to tokens minted later and can still be changed.
+    pub fn freeze_metadata(&mut self) {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        self.metadata_frozen = true;
+        log_whitelist_event(
+            "metadata_freeze",
+            json!([{ "frozen_at": U64(env::block_timestamp()) }]),
+        );
+    }
+
+    pub fn is_metadata_frozen(&self) -> bool {
+        self.metadata_frozen
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_metadata_not_frozen(&self) {
+        assert!(!self.metadata_frozen, "Metadata is frozen");
+    }
+
+    pub(crate) fn internal_metadata(&self) -> NFTContractMetadata {
+        self.metadata
+            .get()
+            .unwrap_or_else(|| env::panic(b"Contract metadata is missing, initialize the contract"))
+    }
+}
+
+/// `base_uri` without its trailing slashes, panics if nothing is left.
+fn normalize_base_uri(base_uri: &str) -> String {
+    let base_uri = base_uri.trim().trim_end_matches('/');
+    assert!(!base_uri.is_empty(), "Base URI can't be empty");
+    base_uri.to_string()
+}
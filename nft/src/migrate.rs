@@ -0,0 +1,142 @@
+use crate::*;
+
+/// Order in which the first release assigned variants to minted tokens.
+const LEGACY_VARIANT_CYCLE: [&str; 5] = ["a", "c", "e", "b", "d"];
+
+/// State layout of the first release, with `u16` counters and no index besides the ones kept
+/// by `NonFungibleToken`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV1 {
+    pub tokens: NonFungibleToken,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub token_minted: u16,
+    pub token_minted_users: u16,
+    pub current_index: u8,
+}
+
+/// Where `backfill_legacy_tokens` stands in the tokens of the first release.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct LegacyBackfill {
+    /// Last token id indexed, tokens being walked in the order of `owner_by_id`.
+    last_token_id: Option<TokenId>,
+    /// Legacy tokens left to index.
+    remaining: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Upgrades the state of the first release. Its tokens keep their ids and are added to the
+    /// indexes by `backfill_legacy_tokens`. Tokens numbered "1" to `token_minted` keep their
+    /// number in the token sequence, tokens minted with other ids get the next free numbers
+    /// once backfilled. Their media, minted as absolute `DEFAULT_BASE_URI` URLs, is made relative
+    /// to `base_uri` as well.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: ContractV1 = env::state_read().expect("Old state doesn't exist");
+        let token_minted = old.token_minted as u64;
+        // the first release could not burn, but tokens could be minted with any id
+        let legacy_tokens = old.tokens.owner_by_id.len();
+
+        let mut this = Self::from_parts(old.tokens, old.metadata);
+        this.token_minted = token_minted;
+        this.token_minted_users = old.token_minted_users as u64;
+        this.current_index = old.current_index;
+        this.circulating_supply = legacy_tokens;
+        this.last_token_sequence = token_minted;
+        if let Some(mut metadata) = this.metadata.get() {
+            if metadata.base_uri.is_none() {
+                metadata.base_uri = Some(DEFAULT_BASE_URI.to_string());
+                this.metadata.set(&metadata);
+            }
+        }
+        if legacy_tokens > 0 {
+            this.legacy_backfill =
+                Some(LegacyBackfill { last_token_id: None, remaining: legacy_tokens });
+        }
+        // Counted upfront so editions continue from the legacy tokens even if minting resumes
+        // before the backfill is done. The first release minted variants in a fixed cycle.
+        for (position, variant) in LEGACY_VARIANT_CYCLE.iter().enumerate() {
+            let count = token_minted / 5 + u64::from((position as u64) < token_minted % 5);
+            if count > 0 {
+                this.variant_minted.insert(variant.to_string(), count);
+            }
+        }
+        this.measure_storage_registration();
+        this
+    }
+
+    /// Indexes up to `limit` tokens minted before the migration. Call until it returns true;
+    /// listings, variant counts and holders miss the remaining legacy tokens until then.
+    pub fn backfill_legacy_tokens(&mut self, limit: u64) -> bool {
+        self.assert_owner();
+        let mut backfill = match self.legacy_backfill.take() {
+            Some(backfill) => backfill,
+            None => return true,
+        };
+        let owners: Box<dyn Iterator<Item = (TokenId, AccountId)>> = match &backfill.last_token_id {
+            Some(last_token_id) => {
+                Box::new(self.tokens.owner_by_id.iter_from(last_token_id.clone()))
+            }
+            None => Box::new(self.tokens.owner_by_id.iter()),
+        };
+        let token_ids: Vec<TokenId> = owners
+            .map(|(token_id, _)| token_id)
+            // tokens minted since the migration are already indexed
+            .filter(|token_id| !self.token_sequence.contains_key(token_id))
+            .take(limit as usize)
+            .collect();
+        let done = token_ids.len() < limit as usize;
+
+        for token_id in token_ids {
+            let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap();
+            let sequence = match token_id.parse::<u64>() {
+                Ok(number) if number >= 1 && number <= self.token_minted => number,
+                _ => {
+                    self.last_token_sequence += 1;
+                    self.last_token_sequence
+                }
+            };
+            let metadata =
+                self.tokens.token_metadata_by_id.as_ref().and_then(|by_id| by_id.get(&token_id));
+            let variant = metadata
+                .as_ref()
+                .and_then(|metadata| metadata.title.as_deref())
+                .and_then(legacy_variant);
+            if let Some(mut metadata) = metadata {
+                let gateway = format!("{}/", DEFAULT_BASE_URI);
+                let media = metadata
+                    .media
+                    .as_deref()
+                    .and_then(|media| media.strip_prefix(&gateway))
+                    .map(String::from);
+                if let Some(media) = media {
+                    metadata.media = Some(media);
+                    self.tokens.token_metadata_by_id.as_mut().unwrap().insert(&token_id, &metadata);
+                }
+            }
+            self.internal_index_token(&token_id, &owner_id, sequence, variant.as_deref());
+            self.internal_sync_holder(&owner_id);
+            backfill.remaining = backfill.remaining.saturating_sub(1);
+            backfill.last_token_id = Some(token_id);
+        }
+        if !done {
+            self.legacy_backfill = Some(backfill);
+        }
+        done
+    }
+
+    /// Number of legacy tokens `backfill_legacy_tokens` still has to index, at most.
+    pub fn legacy_tokens_to_backfill(&self) -> u64 {
+        self.legacy_backfill.as_ref().map_or(0, |backfill| backfill.remaining)
+    }
+}
+
+/// Variant letter in a first release title, e.g. "c" in "HRMS #1c Whitelist NFTs".
+fn legacy_variant(title: &str) -> Option<String> {
+    title
+        .strip_prefix("HRMS #1")
+        .and_then(|rest| rest.chars().next())
+        .filter(|letter| letter.is_ascii_lowercase())
+        .map(String::from)
+}
@@ -0,0 +1,141 @@
+use crate::approval::approvals_storage;
+use crate::*;
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_sdk::{assert_one_yocto, PromiseResult};
+
+/// NEP-171 core methods. They delegate to `NonFungibleToken`, and storage added by a transfer,
+/// such as the receiver's token set, is drawn from the sender's storage balance when registered.
+/// Approvals cleared by a transfer are credited back to the storage balance of their owner.
+#[near_bindgen]
+impl NonFungibleTokenCore for Contract {
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        let sender_id = env::predecessor_account_id();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        self.assert_transferable(&token_id);
+        assert_one_yocto();
+        // they refund their storage to the seller, so it's not counted in the storage used
+        self.internal_remove_listing(&token_id);
+        self.internal_remove_auction(&token_id);
+        let mut initial_storage_usage = env::storage_usage();
+        let (_, approved_account_ids) = self.tokens.internal_transfer(
+            &sender_id,
+            receiver_id.as_ref(),
+            &token_id,
+            approval_id,
+            memo,
+        );
+        if let Some(approved_account_ids) = approved_account_ids {
+            initial_storage_usage -=
+                self.internal_refund_approvals(&owner_id, &approved_account_ids);
+        }
+        self.internal_on_transfer(&token_id, &owner_id, receiver_id.as_ref());
+        self.internal_draw_storage(&sender_id, initial_storage_usage);
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        let sender_id = env::predecessor_account_id();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        self.assert_transferable(&token_id);
+        self.internal_remove_listing(&token_id);
+        self.internal_remove_auction(&token_id);
+        // the cleared approvals are refunded to the owner in `nft_resolve_transfer`
+        let approvals_released = self
+            .tokens
+            .approvals_by_id
+            .as_ref()
+            .and_then(|by_id| by_id.get(&token_id))
+            .map_or(0, |approved_account_ids| approvals_storage(&approved_account_ids));
+        let initial_storage_usage = env::storage_usage() - approvals_released;
+        // kept for `nft_resolve_transfer`, whose arguments are fixed by the standard
+        if let Some(held_since) = self.internal_held_since(&token_id) {
+            self.held_since_in_transfer.insert(&token_id, &held_since);
+        }
+        let result = self.tokens.nft_transfer_call(
+            receiver_id.clone(),
+            token_id.clone(),
+            approval_id,
+            memo,
+            msg,
+        );
+        self.internal_on_transfer(&token_id, &owner_id, receiver_id.as_ref());
+        self.internal_draw_storage(&sender_id, initial_storage_usage);
+        result
+    }
+
+    fn nft_token(self, token_id: TokenId) -> Option<Token> {
+        self.tokens.owner_by_id.get(&token_id).map(|_| self.internal_token(&token_id))
+    }
+
+    fn mint(
+        &mut self,
+        token_id: TokenId,
+        token_owner_id: ValidAccountId,
+        token_metadata: Option<TokenMetadata>,
+    ) -> Token {
+        self.internal_mint(Some(token_id), token_owner_id, token_metadata, None)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    /// Same as `NonFungibleToken::nft_resolve_transfer`, except that approval storage goes
+    /// back through the storage balances, and that the approvals of a token kept by the
+    /// receiver are refunded to the previous owner. A returned token keeps its holding time.
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            _ => true,
+        };
+        let held_since = self.held_since_in_transfer.remove(&token_id);
+        // the receiver keeps the token, unless it already sent it on or burned it
+        if !must_revert || self.tokens.owner_by_id.get(&token_id).as_ref() != Some(&receiver_id) {
+            if let Some(approved_account_ids) = approved_account_ids {
+                self.internal_refund_approvals(&previous_owner_id, &approved_account_ids);
+            }
+            return true;
+        }
+
+        env::log(
+            format!("Return token {} from @{} to @{}", token_id, receiver_id, previous_owner_id)
+                .as_bytes(),
+        );
+        self.tokens.internal_transfer_unguarded(&token_id, &receiver_id, &previous_owner_id);
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let receiver_approvals = approvals_by_id.remove(&token_id);
+        if let Some(approved_account_ids) = approved_account_ids {
+            approvals_by_id.insert(&token_id, &approved_account_ids);
+        }
+        if let Some(receiver_approvals) = receiver_approvals {
+            self.internal_refund_approvals(&receiver_id, &receiver_approvals);
+        }
+        self.internal_on_transfer(&token_id, &receiver_id, &previous_owner_id);
+        self.internal_restore_held_since(&token_id, held_since);
+        false
+    }
+}
@@ -0,0 +1,242 @@
+use crate::events::{log_nft_event, log_whitelist_event};
+use crate::external::{ext_pfp, ext_self};
+use crate::*;
+use near_sdk::json_types::U64;
+use near_sdk::{assert_one_yocto, Balance, PromiseResult};
+
+const GAS_FOR_PFP_CLAIM: Gas = 25_000_000_000_000;
+const GAS_FOR_RESOLVE_REDEEM: Gas = 25_000_000_000_000;
+const NO_DEPOSIT: Balance = 0;
+
+/// Record of a used whitelist right, either burned in exchange for a claim on the PFP drop or
+/// marked as used by a redeemer contract.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Redemption {
+    /// Owner of the token when it was redeemed.
+    pub owner_id: AccountId,
+    /// Account the whitelist right was used for.
+    pub target_id: AccountId,
+    /// Contract that consumed the whitelist right.
+    pub redeemer_id: AccountId,
+    /// Whether the token was burned or kept by its owner.
+    pub burned: bool,
+    pub redeemed_at: U64,
+}
+
+/// Summary of the whitelist tokens held by an account, see `whitelist_status`.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WhitelistStatus {
+    /// Number of tokens held.
+    pub held: u64,
+    /// Number of held tokens whose whitelist right is still unused.
+    pub unredeemed: u64,
+    pub token_ids: Vec<TokenId>,
+    pub unredeemed_token_ids: Vec<TokenId>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_pfp_contract(&mut self, pfp_contract_id: ValidAccountId) {
+        self.assert_owner();
+        self.pfp_contract_id = Some(pfp_contract_id.into());
+    }
+
+    pub fn get_pfp_contract(&self) -> Option<AccountId> {
+        self.pfp_contract_id.clone()
+    }
+
+    /// Allows `account_id` to mark tokens as redeemed with `mark_redeemed`.
+    pub fn add_redeemer(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.redeemers.insert(account_id.as_ref());
+    }
+
+    pub fn remove_redeemer(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.redeemers.remove(account_id.as_ref());
+    }
+
+    pub fn get_redeemers(&self) -> Vec<AccountId> {
+        self.redeemers.to_vec()
+    }
+
+    /// Redeems `token_id` for a PFP claimed on behalf of `target`.
+    ///
+    /// The token is held by this contract while the PFP contract is called. It is burned once
+    /// the claim succeeds and handed back to its owner if the claim fails. Only valid tokens can
+    /// be redeemed, see `is_valid`.
+    #[payable]
+    pub fn redeem(&mut self, token_id: TokenId, target: ValidAccountId) -> Promise {
+        assert_one_yocto();
+        let pfp_contract_id = self.pfp_contract_id.clone().expect("PFP contract is not configured");
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            owner_id,
+            "Only the token owner can redeem the token"
+        );
+        assert!(!self.redemptions.contains_key(&token_id), "Token was already redeemed");
+        self.assert_valid_token(&token_id);
+        self.assert_no_bids(&token_id);
+
+        let (_, approved_account_ids) = self.tokens.internal_transfer(
+            &owner_id,
+            &env::current_account_id(),
+            &token_id,
+            None,
+            None,
+        );
+        if let Some(approved_account_ids) = approved_account_ids {
+            self.internal_refund_approvals(&owner_id, &approved_account_ids);
+        }
+        if let Some(held_since) = self.internal_held_since(&token_id) {
+            self.held_since_in_transfer.insert(&token_id, &held_since);
+        }
+        self.internal_on_transfer(&token_id, &owner_id, &env::current_account_id());
+
+        ext_pfp::nft_claim(
+            target.to_string(),
+            token_id.clone(),
+            &pfp_contract_id,
+            NO_DEPOSIT,
+            GAS_FOR_PFP_CLAIM,
+        )
+        .then(ext_self::resolve_redeem(
+            token_id,
+            owner_id,
+            target.into(),
+            pfp_contract_id,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_REDEEM,
+        ))
+    }
+
+    /// Burns the escrowed token if the PFP claim succeeded, otherwise returns it to `owner_id`
+    /// with the holding time it had before the escrow. Returns true if the token was redeemed.
+    #[private]
+    pub fn resolve_redeem(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        target_id: AccountId,
+        redeemer_id: AccountId,
+    ) -> bool {
+        let escrow_id = env::current_account_id();
+        let held_since = self.held_since_in_transfer.remove(&token_id);
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                self.internal_burn(&token_id, &escrow_id, &owner_id);
+                self.redemptions.insert(
+                    &token_id,
+                    &Redemption {
+                        owner_id: owner_id.clone(),
+                        target_id: target_id.clone(),
+                        redeemer_id: redeemer_id.clone(),
+                        burned: true,
+                        redeemed_at: env::block_timestamp().into(),
+                    },
+                );
+                log_nft_event(
+                    "nft_burn",
+                    json!([{ "owner_id": owner_id, "token_ids": [token_id] }]),
+                );
+                log_whitelist_event(
+                    "whitelist_redeem",
+                    json!([{
+                        "owner_id": owner_id,
+                        "target_id": target_id,
+                        "redeemer_id": redeemer_id,
+                        "token_ids": [token_id],
+                        "burned": true,
+                    }]),
+                );
+                true
+            }
+            _ => {
+                env::log(
+                    format!("Redeem of token {} failed, returning it to {}", token_id, owner_id)
+                        .as_bytes(),
+                );
+                self.tokens.internal_transfer_unguarded(&token_id, &escrow_id, &owner_id);
+                self.internal_on_transfer(&token_id, &escrow_id, &owner_id);
+                self.internal_restore_held_since(&token_id, held_since);
+                false
+            }
+        }
+    }
+
+    /// Marks the whitelist right of `token_id` as used while its owner keeps the token.
+    /// Only callable by a redeemer contract, while the token is valid. Marking an already
+    /// redeemed token does nothing.
+    /// Returns true if the token was marked by this call.
+    pub fn mark_redeemed(&mut self, token_id: TokenId) -> bool {
+        let redeemer_id = env::predecessor_account_id();
+        assert!(
+            self.redeemers.contains(&redeemer_id),
+            "Only a redeemer can mark tokens as redeemed"
+        );
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        if self.redemptions.contains_key(&token_id) {
+            return false;
+        }
+        self.assert_valid_token(&token_id);
+
+        self.redemptions.insert(
+            &token_id,
+            &Redemption {
+                owner_id: owner_id.clone(),
+                target_id: owner_id.clone(),
+                redeemer_id: redeemer_id.clone(),
+                burned: false,
+                redeemed_at: env::block_timestamp().into(),
+            },
+        );
+        log_whitelist_event(
+            "whitelist_redeem",
+            json!([{
+                "owner_id": owner_id,
+                "target_id": owner_id,
+                "redeemer_id": redeemer_id,
+                "token_ids": [token_id],
+                "burned": false,
+            }]),
+        );
+        true
+    }
+
+    pub fn is_redeemed(&self, token_id: TokenId) -> bool {
+        self.redemptions.contains_key(&token_id)
+    }
+
+    pub fn get_redemption(&self, token_id: TokenId) -> Option<Redemption> {
+        self.redemptions.get(&token_id)
+    }
+
+    /// Whitelist tokens held by `account_id` and how many of them are still unredeemed.
+    ///
+    /// Meant to be called through a promise by the PFP sale contract: it only reads the owner's
+    /// token set and the redemption registry, never the token metadata.
+    pub fn whitelist_status(&self, account_id: ValidAccountId) -> WhitelistStatus {
+        let token_ids: Vec<TokenId> = self
+            .tokens
+            .tokens_per_owner
+            .as_ref()
+            .and_then(|by_owner| by_owner.get(account_id.as_ref()))
+            .map(|owner_tokens| owner_tokens.to_vec())
+            .unwrap_or_default();
+        let unredeemed_token_ids: Vec<TokenId> = token_ids
+            .iter()
+            .filter(|token_id| !self.redemptions.contains_key(token_id))
+            .cloned()
+            .collect();
+        WhitelistStatus {
+            held: token_ids.len() as u64,
+            unredeemed: unredeemed_token_ids.len() as u64,
+            token_ids,
+            unredeemed_token_ids,
+        }
+    }
+}
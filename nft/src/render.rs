@@ -0,0 +1,105 @@
+use crate::*;
+use near_sdk::serde_json::{self, Map, Value};
+
+const SVG_SIZE: u32 = 500;
+/// Background of tokens whose variant is not in `VARIANT_COLORS`.
+const FALLBACK_COLOR: &str = "#444444";
+/// Background colour of each variant's on-chain artwork.
+const VARIANT_COLORS: [(&str, &str); 5] =
+    [("a", "#00b7b2"), ("b", "#ff6000"), ("c", "#7b3fe4"), ("d", "#e4387b"), ("e", "#2f80ed")];
+
+#[near_bindgen]
+impl Contract {
+    /// Makes `nft_token` and the listings return `media` as an SVG rendered on chain from each
+    /// token's variant and edition instead of the stored image.
+    pub fn set_on_chain_media(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.assert_metadata_not_frozen();
+        let mut config = self.internal_config();
+        config.on_chain_media = enabled;
+        self.config.set(&config);
+    }
+}
+
+impl Contract {
+    /// Swaps the stored media of `token_id` for its on-chain artwork when the collection is
+    /// set to render media on chain.
+    pub(crate) fn internal_render_media(&self, token_id: &TokenId, metadata: &mut TokenMetadata) {
+        if !self.internal_config().on_chain_media {
+            return;
+        }
+        let extra: Map<String, Value> = metadata
+            .extra
+            .as_deref()
+            .and_then(|extra| serde_json::from_str(extra).ok())
+            .unwrap_or_default();
+        let variant = self
+            .token_variants
+            .get(token_id)
+            .or_else(|| extra.get("variant").and_then(Value::as_str).map(String::from));
+        // legacy tokens have no edition, they show their token number instead
+        let number = match extra.get("edition").and_then(Value::as_u64) {
+            Some(edition) => match extra.get("edition_size").and_then(Value::as_u64) {
+                Some(edition_size) => format!("{}/{}", edition, edition_size),
+                None => edition.to_string(),
+            },
+            None => self
+                .token_sequence
+                .get(token_id)
+                .map(|sequence| sequence.to_string())
+                .unwrap_or_default(),
+        };
+        let svg = render_token_svg(variant.as_deref(), &number);
+        metadata.media = Some(svg_data_uri(&svg));
+        metadata.media_hash = None;
+    }
+}
+
+/// Artwork of a token of `variant`, labelled with its `number`. It only depends on the token,
+/// not on who holds it.
+pub(crate) fn render_token_svg(variant: Option<&str>, number: &str) -> String {
+    let color = variant
+        .and_then(|variant| VARIANT_COLORS.iter().find(|(letter, _)| *letter == variant))
+        .map_or(FALLBACK_COLOR, |(_, color)| color);
+    let label = format!("HRMS #1{}", variant.unwrap_or_default());
+    format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' width='{size}' height='{size}' \
+         viewBox='0 0 {size} {size}'>\
+         <rect width='{size}' height='{size}' fill='{color}'/>\
+         <rect x='20' y='20' width='460' height='460' rx='24' fill='none' stroke='#fff' \
+         stroke-width='6'/>\
+         <text x='250' y='230' font-family='monospace' font-size='72' text-anchor='middle' \
+         fill='#fff'>{label}</text>\
+         <text x='250' y='320' font-family='monospace' font-size='48' text-anchor='middle' \
+         fill='#fff'>{number}</text>\
+         </svg>",
+        size = SVG_SIZE,
+        color = color,
+        label = escape_xml(&label),
+        number = escape_xml(number),
+    )
+}
+
+/// `svg` as a `data:` URI, percent-encoding the characters that are not allowed in a URI.
+pub(crate) fn svg_data_uri(svg: &str) -> String {
+    let mut uri = String::from("data:image/svg+xml,");
+    for c in svg.chars() {
+        match c {
+            '%' => uri.push_str("%25"),
+            '#' => uri.push_str("%23"),
+            '<' => uri.push_str("%3C"),
+            '>' => uri.push_str("%3E"),
+            '"' => uri.push_str("%22"),
+            _ => uri.push(c),
+        }
+    }
+    uri
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,149 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::json_types::U128;
+use std::collections::HashMap;
+
+/// Royalties are expressed in basis points, 10_000 being the whole sale price.
+pub const ROYALTY_TOTAL: u32 = 10_000;
+/// Upper bound for the collection royalty and for a variant override taken on its own,
+/// so that the merged royalty of a token can never exceed the sale price.
+pub const MAX_ROYALTY: u32 = ROYALTY_TOTAL / 2;
+
+/// Amounts owed to each account from a sale, as defined by NEP-199.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    pub payout: HashMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Replaces the collection-wide royalty paid out on every token.
+    pub fn set_royalty(&mut self, royalty: HashMap<AccountId, u32>) {
+        self.assert_owner();
+        assert_valid_royalty(&royalty);
+        self.royalty = royalty;
+    }
+
+    /// Sets the guest artist royalty of `variant`. It only applies to tokens minted afterwards,
+    /// and an empty map removes the override.
+    pub fn set_variant_royalty(&mut self, variant: String, royalty: HashMap<AccountId, u32>) {
+        self.assert_owner();
+        assert_valid_royalty(&royalty);
+        if royalty.is_empty() {
+            self.variant_royalties.remove(&variant);
+        } else {
+            self.variant_royalties.insert(&variant, &royalty);
+        }
+    }
+
+    pub fn get_variant_royalty(&self, variant: String) -> HashMap<AccountId, u32> {
+        self.variant_royalties.get(&variant).unwrap_or_default()
+    }
+
+    /// Effective royalty of `token_id`: the collection default merged with its overrides.
+    pub fn nft_royalty(&self, token_id: TokenId) -> HashMap<AccountId, u32> {
+        self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        self.internal_royalty(&token_id)
+    }
+
+    pub fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: u32) -> Payout {
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        self.internal_payout(&token_id, &owner_id, balance.into(), max_len_payout)
+    }
+
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: u32,
+    ) -> Payout {
+        assert_one_yocto();
+        self.assert_transferable(&token_id);
+        let sender_id = env::predecessor_account_id();
+        // they refund their storage to the seller, so it's not counted in the storage used
+        self.internal_remove_listing(&token_id);
+        self.internal_remove_auction(&token_id);
+        let mut initial_storage_usage = env::storage_usage();
+        let (previous_owner_id, approved_account_ids) = self.tokens.internal_transfer(
+            &sender_id,
+            receiver_id.as_ref(),
+            &token_id,
+            approval_id,
+            memo,
+        );
+        if let Some(approved_account_ids) = approved_account_ids {
+            initial_storage_usage -=
+                self.internal_refund_approvals(&previous_owner_id, &approved_account_ids);
+        }
+        self.internal_on_transfer(&token_id, &previous_owner_id, receiver_id.as_ref());
+        self.internal_draw_storage(&sender_id, initial_storage_usage);
+        self.internal_payout(&token_id, &previous_owner_id, balance.into(), max_len_payout)
+    }
+}
+
+impl Contract {
+    /// Collection royalty with the token's own overrides applied on top of it.
+    pub(crate) fn internal_royalty(&self, token_id: &TokenId) -> HashMap<AccountId, u32> {
+        let mut royalty = self.royalty.clone();
+        if let Some(overrides) = self.token_royalties.get(token_id) {
+            royalty.extend(overrides);
+        }
+        royalty
+    }
+
+    /// Splits `balance` between the royalty receivers of `token_id`, the rest going to `owner_id`.
+    pub(crate) fn internal_payout(
+        &self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        balance: u128,
+        max_len_payout: u32,
+    ) -> Payout {
+        let royalty = self.internal_royalty(token_id);
+        assert!(
+            royalty.len() < max_len_payout as usize,
+            "Market cannot payout to that many receivers"
+        );
+
+        let mut payout: HashMap<AccountId, u128> = HashMap::new();
+        let mut royalties_paid = 0;
+        for (account_id, basis_points) in royalty {
+            let amount = royalty_to_payout(basis_points, balance);
+            royalties_paid += amount;
+            *payout.entry(account_id).or_insert(0) += amount;
+        }
+        *payout.entry(owner_id.clone()).or_insert(0) += balance - royalties_paid;
+
+        Payout {
+            payout: payout
+                .into_iter()
+                .map(|(account_id, amount)| (account_id, U128(amount)))
+                .collect(),
+        }
+    }
+
+    /// Sends the `price` of a sale made through this contract to the royalty receivers of
+    /// `token_id` and to `seller_id`.
+    pub(crate) fn internal_pay_sale(&self, token_id: &TokenId, seller_id: &AccountId, price: u128) {
+        let payout = self.internal_payout(token_id, seller_id, price, u32::MAX);
+        for (account_id, amount) in payout.payout {
+            if amount.0 > 0 {
+                Promise::new(account_id).transfer(amount.0);
+            }
+        }
+    }
+}
+
+fn assert_valid_royalty(royalty: &HashMap<AccountId, u32>) {
+    let total: u32 = royalty.values().sum();
+    assert!(total <= MAX_ROYALTY, "Royalty cannot exceed {} basis points", MAX_ROYALTY);
+}
+
+fn royalty_to_payout(basis_points: u32, balance: u128) -> u128 {
+    balance * u128::from(basis_points) / u128::from(ROYALTY_TOTAL)
+}
@@ -0,0 +1,27 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Binds `token_id` to its current owner, or releases it. Soulbound tokens can't be
+    /// transferred or approved, only burned or redeemed.
+    pub fn set_token_transferable(&mut self, token_id: TokenId, transferable: bool) {
+        self.assert_owner();
+        assert!(self.tokens.owner_by_id.contains_key(&token_id), "Token not found");
+        if transferable {
+            self.soulbound_tokens.remove(&token_id);
+        } else {
+            self.soulbound_tokens.insert(&token_id);
+        }
+    }
+
+    /// Whether `token_id` can change hands, false for soulbound tokens.
+    pub fn is_transferable(&self, token_id: TokenId) -> bool {
+        !self.soulbound_tokens.contains(&token_id)
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_soulbound(&self, token_id: &TokenId) {
+        assert!(!self.soulbound_tokens.contains(token_id), "Token is soulbound");
+    }
+}
@@ -0,0 +1,44 @@
+use crate::*;
+
+/// Everything the mint page shows, see `mint_stats`.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintStats {
+    pub total_minted: u64,
+    /// Tokens minted through `nft_mint` by accounts other than the contract.
+    pub user_minted: u64,
+    /// Tokens minted by the contract account for itself.
+    pub reserve_minted: u64,
+    /// Tokens minted so far for each variant letter, burned ones included.
+    pub variant_minted: HashMap<String, u64>,
+    pub circulating_supply: u64,
+    pub remaining_supply: u64,
+    /// Tokens still on sale to users, bounded by `remaining_supply`.
+    pub remaining_user_supply: u64,
+    pub mint_price: U128,
+    pub proceeds_collected: U128,
+    /// Part of `proceeds_collected` sent out with `withdraw_proceeds`.
+    pub proceeds_withdrawn: U128,
+    pub unique_holders: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn mint_stats(&self) -> MintStats {
+        let remaining_supply = MAX_NFT_MINT.saturating_sub(self.token_minted);
+        let remaining_user_supply = MAX_NFT_MINT_USERS.saturating_sub(self.token_minted_users);
+        MintStats {
+            total_minted: self.token_minted,
+            user_minted: self.token_minted_users,
+            reserve_minted: self.token_minted - self.token_minted_users,
+            variant_minted: self.variant_minted.clone(),
+            circulating_supply: self.circulating_supply,
+            remaining_supply,
+            remaining_user_supply: remaining_user_supply.min(remaining_supply),
+            mint_price: U128(MINT_PRICE),
+            proceeds_collected: U128(self.proceeds_collected),
+            proceeds_withdrawn: U128(self.proceeds_withdrawn),
+            unique_holders: self.holders.len(),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::*;
+use near_contract_standards::non_fungible_token::refund_deposit;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_sdk::assert_one_yocto;
+use near_sdk::json_types::U128;
+
+/// NEP-145 storage management. Registered accounts prepay storage once, and approvals and
+/// transfers are then paid from that balance instead of from per-call deposits.
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<ValidAccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id: AccountId = account_id
+            .map(|account_id| account_id.into())
+            .unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+
+        if let Some(available) = self.storage_deposits.get(&account_id) {
+            if registration_only {
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+            } else {
+                self.storage_deposits.insert(&account_id, &(available + amount));
+            }
+        } else {
+            let min_balance = self.storage_balance_bounds().min.0;
+            assert!(
+                amount >= min_balance,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            let mut available = amount - min_balance;
+            if registration_only && available > 0 {
+                Promise::new(env::predecessor_account_id()).transfer(available);
+                available = 0;
+            }
+            self.storage_deposits.insert(&account_id, &available);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let available = self.storage_deposits.get(&account_id).unwrap_or_else(|| {
+            env::panic(format!("The account {} is not registered", &account_id).as_bytes())
+        });
+        let amount = amount.map_or(available, |amount| amount.0);
+        assert!(amount <= available, "The amount is greater than the available storage balance");
+        self.storage_deposits.insert(&account_id, &(available - amount));
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    /// Refunds the whole available balance and the registration deposit. An account still
+    /// owning tokens can only unregister with `force`, its tokens then staying with it while
+    /// the storage they'll free is refunded as NEAR.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let owns_tokens = self
+            .tokens
+            .tokens_per_owner
+            .as_ref()
+            .and_then(|per_owner| per_owner.get(&account_id))
+            .map_or(false, |tokens| !tokens.is_empty());
+        assert!(
+            !owns_tokens || force.unwrap_or(false),
+            "Can't unregister an account that still owns tokens without force"
+        );
+        if let Some(available) = self.storage_deposits.remove(&account_id) {
+            Promise::new(account_id).transfer(available + self.storage_balance_bounds().min.0);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(Balance::from(self.storage_registration_bytes) * env::storage_byte_cost()),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.internal_storage_balance_of(account_id.as_ref())
+    }
+}
+
+impl Contract {
+    pub(crate) fn measure_storage_registration(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = "a".repeat(64);
+        self.storage_deposits.insert(&tmp_account_id, &0);
+        self.storage_registration_bytes = env::storage_usage() - initial_storage_usage;
+        self.storage_deposits.remove(&tmp_account_id);
+    }
+
+    fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
+        self.storage_deposits.get(account_id).map(|available| StorageBalance {
+            total: U128(available + self.storage_balance_bounds().min.0),
+            available: U128(available),
+        })
+    }
+
+    /// Pays for `storage_used` bytes added on behalf of `account_id`, the predecessor.
+    /// Registered accounts pay from their storage balance, topped up with the attached deposit.
+    /// Other accounts must attach the cost and get the excess refunded.
+    pub(crate) fn internal_charge_storage(
+        &mut self,
+        account_id: &AccountId,
+        storage_used: StorageUsage,
+    ) {
+        if let Some(available) = self.storage_deposits.get(account_id) {
+            let required_cost = Balance::from(storage_used) * env::storage_byte_cost();
+            let available = available + env::attached_deposit();
+            assert!(
+                required_cost <= available,
+                "Not enough storage balance, deposit {} yoctoNEAR with storage_deposit",
+                required_cost - available,
+            );
+            self.storage_deposits.insert(account_id, &(available - required_cost));
+        } else {
+            refund_deposit(storage_used);
+        }
+    }
+
+    /// Returns the cost of `storage_released` bytes to `account_id`, crediting its storage
+    /// balance if registered.
+    pub(crate) fn internal_refund_storage(
+        &mut self,
+        account_id: &AccountId,
+        storage_released: StorageUsage,
+    ) {
+        let refund = Balance::from(storage_released) * env::storage_byte_cost();
+        if let Some(available) = self.storage_deposits.get(account_id) {
+            self.storage_deposits.insert(account_id, &(available + refund));
+        } else if refund > 0 {
+            Promise::new(account_id.clone()).transfer(refund);
+        }
+    }
+
+    /// Draws the storage added since `initial_storage_usage` from the balance of `account_id`,
+    /// as far as it goes. The contract keeps covering it for accounts that aren't registered.
+    pub(crate) fn internal_draw_storage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        if storage_used == 0 {
+            return;
+        }
+        if let Some(available) = self.storage_deposits.get(account_id) {
+            let cost = Balance::from(storage_used) * env::storage_byte_cost();
+            self.storage_deposits.insert(account_id, &available.saturating_sub(cost));
+        }
+    }
+}
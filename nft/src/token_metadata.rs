@@ -0,0 +1,123 @@
+use crate::events::log_whitelist_event;
+use crate::internal::set_extra_field;
+use crate::*;
+use near_sdk::serde_json::{self, Map, Value};
+
+/// Keys of `extra` written by the contract itself, which updates can't overwrite.
+const RESERVED_EXTRA_KEYS: [&str; 7] =
+    ["variant", "edition", "edition_size", "attributes", "redeemed", "transfer_unlocks_at", "tier"];
+
+/// Changes to a token's metadata, see `nft_update_token_metadata`. Fields left out are kept.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadataUpdate {
+    pub description: Option<String>,
+    /// Path relative to `base_uri` or absolute URL, like the media given at mint.
+    pub media: Option<String>,
+    /// JSON object whose keys are set in the token's `extra`, other keys are kept. Keys written
+    /// by the contract, such as `variant` or `edition`, can't be set.
+    pub extra: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allows `account_id` to update and lock token metadata.
+    pub fn add_metadata_updater(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.metadata_updaters.insert(account_id.as_ref());
+    }
+
+    pub fn remove_metadata_updater(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.metadata_updaters.remove(account_id.as_ref());
+    }
+
+    pub fn get_metadata_updaters(&self) -> Vec<AccountId> {
+        self.metadata_updaters.to_vec()
+    }
+
+    /// Updates the metadata of `token_id` and sets its `updated_at`. Callable by the owner or a
+    /// metadata updater until the token's metadata is locked. Storage added by the update is
+    /// paid by the caller, like an approval.
+    #[payable]
+    pub fn nft_update_token_metadata(
+        &mut self,
+        token_id: TokenId,
+        update: TokenMetadataUpdate,
+    ) -> TokenMetadata {
+        self.assert_metadata_updater();
+        assert!(!self.is_token_metadata_locked(token_id.clone()), "Token metadata is locked");
+        let initial_storage_usage = env::storage_usage();
+
+        let token_metadata_by_id = self.tokens.token_metadata_by_id.as_ref().unwrap();
+        let mut metadata = token_metadata_by_id.get(&token_id).expect("Token not found");
+        let mut fields = vec![];
+        if let Some(description) = update.description {
+            metadata.description = Some(description);
+            fields.push("description");
+        }
+        if let Some(media) = update.media {
+            metadata.media = Some(media);
+            metadata.media_hash = None;
+            fields.push("media");
+        }
+        if let Some(extra) = update.extra {
+            let changes: Map<String, Value> =
+                serde_json::from_str(&extra).expect("Extra must be a JSON object");
+            for (key, value) in changes {
+                assert!(
+                    !RESERVED_EXTRA_KEYS.contains(&key.as_str()),
+                    "Extra key {} is reserved",
+                    key
+                );
+                set_extra_field(&mut metadata, &key, value);
+            }
+            fields.push("extra");
+        }
+        assert!(!fields.is_empty(), "Nothing to update");
+        metadata.updated_at = Some(env::block_timestamp().to_string());
+        self.tokens.token_metadata_by_id.as_mut().unwrap().insert(&token_id, &metadata);
+
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        self.internal_charge_storage(&env::predecessor_account_id(), storage_used);
+        log_whitelist_event(
+            "token_metadata_update",
+            json!([{ "token_ids": [token_id], "fields": fields }]),
+        );
+        metadata
+    }
+
+    /// Permanently prevents updates to the metadata of `token_id`.
+    pub fn lock_token_metadata(&mut self, token_id: TokenId) {
+        self.assert_metadata_updater();
+        assert!(self.tokens.owner_by_id.contains_key(&token_id), "Token not found");
+        if self.locked_token_metadata.insert(&token_id) {
+            log_whitelist_event("token_metadata_lock", json!([{ "token_ids": [token_id] }]));
+        }
+    }
+
+    /// Permanently prevents updates to the metadata of every token.
+    pub fn lock_all_token_metadata(&mut self) {
+        self.assert_metadata_updater();
+        if !self.token_metadata_locked {
+            self.token_metadata_locked = true;
+            log_whitelist_event("token_metadata_lock", json!([{ "all_tokens": true }]));
+        }
+    }
+
+    pub fn is_token_metadata_locked(&self, token_id: TokenId) -> bool {
+        self.metadata_frozen
+            || self.token_metadata_locked
+            || self.locked_token_metadata.contains(&token_id)
+    }
+}
+
+impl Contract {
+    fn assert_metadata_updater(&self) {
+        let account_id = env::predecessor_account_id();
+        assert!(
+            account_id == self.tokens.owner_id || self.metadata_updaters.contains(&account_id),
+            "Only the contract owner or a metadata updater can call this method"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Locks tokens minted from now on in place for `duration_ns` nanoseconds after their mint.
+    /// `None` turns the lock off for new tokens, tokens already locked stay locked.
+    pub fn set_transfer_lock(&mut self, duration_ns: Option<U64>) {
+        self.assert_owner();
+        let mut config = self.internal_config();
+        config.transfer_lock_ns = duration_ns;
+        self.config.set(&config);
+    }
+
+    /// When `token_id` can first be transferred, if it was minted during a transfer lock. Also
+    /// written to the token's `extra` as `transfer_unlocks_at`.
+    pub fn transfer_unlocks_at(&self, token_id: TokenId) -> Option<U64> {
+        self.transfer_unlocks_at.get(&token_id).map(U64)
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_transfer_unlocked(&self, token_id: &TokenId) {
+        if let Some(unlocks_at) = self.transfer_unlocks_at.get(token_id) {
+            assert!(env::block_timestamp() >= unlocks_at, "Token is locked until {}", unlocks_at);
+        }
+    }
+}
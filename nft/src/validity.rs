@@ -0,0 +1,55 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the window, in nanoseconds, during which the whitelist rights of tokens minted from
+    /// now on can be used. With `enforce_on_transfer`, tokens outside their window can't be
+    /// transferred either.
+    pub fn set_validity_window(
+        &mut self,
+        starts_at: Option<U64>,
+        expires_at: Option<U64>,
+        enforce_on_transfer: bool,
+    ) {
+        self.assert_owner();
+        if let (Some(starts_at), Some(expires_at)) = (starts_at, expires_at) {
+            assert!(starts_at.0 < expires_at.0, "Validity window must start before it expires");
+        }
+        let mut config = self.internal_config();
+        config.starts_at = starts_at;
+        config.expires_at = expires_at;
+        config.enforce_validity_on_transfer = enforce_on_transfer;
+        self.config.set(&config);
+    }
+
+    /// Whether the whitelist right of `token_id` can be used now, according to the `starts_at`
+    /// and `expires_at` of its metadata. Tokens without them are always valid.
+    pub fn is_valid(&self, token_id: TokenId) -> bool {
+        self.internal_validity_error(&token_id).is_none()
+    }
+}
+
+impl Contract {
+    /// Why `token_id` is outside its validity window, if it is.
+    pub(crate) fn internal_validity_error(&self, token_id: &TokenId) -> Option<&'static str> {
+        assert!(self.tokens.owner_by_id.contains_key(token_id), "Token not found");
+        let metadata =
+            self.tokens.token_metadata_by_id.as_ref().and_then(|by_id| by_id.get(token_id))?;
+        let now = env::block_timestamp();
+        let parse = |timestamp: Option<String>| timestamp.and_then(|t| t.parse::<u64>().ok());
+        if parse(metadata.starts_at).map_or(false, |starts_at| now < starts_at) {
+            Some("Token is not valid yet")
+        } else if parse(metadata.expires_at).map_or(false, |expires_at| now >= expires_at) {
+            Some("Token has expired")
+        } else {
+            None
+        }
+    }
+
+    /// Panics unless the whitelist right of `token_id` can be used now.
+    pub(crate) fn assert_valid_token(&self, token_id: &TokenId) {
+        if let Some(error) = self.internal_validity_error(token_id) {
+            env::panic(error.as_bytes());
+        }
+    }
+}
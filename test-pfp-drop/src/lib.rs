@@ -0,0 +1,55 @@
+/*!
+A stub PFP drop contract that implements nft_claim for simulation testing redeem.
+*/
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::ValidAccountId;
+use near_sdk::{env, log, near_bindgen, setup_alloc, AccountId, BorshStorageKey, PanicOnDefault};
+
+setup_alloc!();
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Claims,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct PfpDrop {
+    whitelist_account_id: AccountId,
+    claims: LookupMap<AccountId, TokenId>,
+}
+
+#[near_bindgen]
+impl PfpDrop {
+    #[init]
+    pub fn new(whitelist_account_id: ValidAccountId) -> Self {
+        Self {
+            whitelist_account_id: whitelist_account_id.into(),
+            claims: LookupMap::new(StorageKey::Claims),
+        }
+    }
+
+    /// Records a claim for `receiver_id`.
+    /// Panics if `receiver_id` already claimed, which should return the whitelist token.
+    pub fn nft_claim(&mut self, receiver_id: AccountId, whitelist_token_id: TokenId) {
+        // Verifying that we were called by the whitelist contract that we expect.
+        assert_eq!(
+            &env::predecessor_account_id(),
+            &self.whitelist_account_id,
+            "Only supports the one whitelist contract"
+        );
+        log!(
+            "in nft_claim; receiver_id={}, whitelist_token_id={}",
+            &receiver_id,
+            &whitelist_token_id
+        );
+        assert!(self.claims.get(&receiver_id).is_none(), "Receiver already claimed");
+        self.claims.insert(&receiver_id, &whitelist_token_id);
+    }
+
+    pub fn get_claim(&self, receiver_id: ValidAccountId) -> Option<TokenId> {
+        self.claims.get(receiver_id.as_ref())
+    }
+}
@@ -0,0 +1,83 @@
+/*!
+A stub PFP sale contract that checks whitelist_status through a promise for simulation testing.
+*/
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::ValidAccountId;
+use near_sdk::serde::Deserialize;
+use near_sdk::{
+    env, ext_contract, log, near_bindgen, setup_alloc, AccountId, Balance, Gas, PanicOnDefault,
+    Promise,
+};
+
+setup_alloc!();
+
+const GAS_FOR_WHITELIST_STATUS: Gas = 10_000_000_000_000;
+const GAS_FOR_ON_WHITELIST_STATUS: Gas = 10_000_000_000_000;
+
+const NO_DEPOSIT: Balance = 0;
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WhitelistStatus {
+    pub held: u64,
+    pub unredeemed: u64,
+    pub token_ids: Vec<TokenId>,
+    pub unredeemed_token_ids: Vec<TokenId>,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct WhitelistCaller {
+    non_fungible_token_account_id: AccountId,
+}
+
+// Defining cross-contract interfaces. This allows to create new promises.
+#[ext_contract(ext_whitelist)]
+pub trait Whitelist {
+    fn whitelist_status(&self, account_id: ValidAccountId) -> WhitelistStatus;
+}
+
+#[ext_contract(ext_self)]
+pub trait WhitelistCallback {
+    fn on_whitelist_status(&self, account_id: AccountId) -> bool;
+}
+
+#[near_bindgen]
+impl WhitelistCaller {
+    #[init]
+    pub fn new(non_fungible_token_account_id: ValidAccountId) -> Self {
+        Self { non_fungible_token_account_id: non_fungible_token_account_id.into() }
+    }
+
+    /// Resolves to true if `account_id` holds at least one unredeemed whitelist token.
+    pub fn check_whitelist(&self, account_id: ValidAccountId) -> Promise {
+        ext_whitelist::whitelist_status(
+            account_id.clone(),
+            &self.non_fungible_token_account_id,
+            NO_DEPOSIT,
+            GAS_FOR_WHITELIST_STATUS,
+        )
+        .then(ext_self::on_whitelist_status(
+            account_id.into(),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_ON_WHITELIST_STATUS,
+        ))
+    }
+
+    #[private]
+    pub fn on_whitelist_status(
+        &self,
+        account_id: AccountId,
+        #[callback] status: WhitelistStatus,
+    ) -> bool {
+        log!(
+            "in on_whitelist_status; account_id={}, held={}, unredeemed={}",
+            account_id,
+            status.held,
+            status.unredeemed
+        );
+        status.unredeemed > 0
+    }
+}
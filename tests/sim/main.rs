@@ -1,4 +1,6 @@
 mod test_approval;
 mod test_core;
 mod test_enumeration;
+mod test_redeem;
+mod test_whitelist;
 mod utils;
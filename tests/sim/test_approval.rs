@@ -1,8 +1,19 @@
 use crate::utils::{init, TOKEN_ID};
 use near_contract_standards::non_fungible_token::Token;
-use near_sdk_sim::{call, view};
+use near_sdk::json_types::U128;
+use near_sdk::serde::Deserialize;
+use near_sdk_sim::{call, to_yocto, view};
 use std::collections::HashMap;
 
+// `StorageBalance` from near-contract-standards only implements `Serialize`.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct StorageBalance {
+    #[allow(dead_code)]
+    total: U128,
+    available: U128,
+}
+
 #[test]
 fn simulate_simple_approve() {
     let (root, nft, alice, token_receiver, _) = init();
@@ -209,3 +220,31 @@ fn simulate_revoke_all() {
             .unwrap_json();
     assert!(!token_receiver_approved);
 }
+
+#[test]
+fn simulate_approve_with_storage_deposit() {
+    let (root, nft, alice, token_receiver, _) = init();
+
+    // root prepays storage once...
+    call!(root, nft.storage_deposit(None, None), deposit = to_yocto("0.01")).assert_success();
+    let balance: StorageBalance =
+        view!(nft.storage_balance_of(root.valid_account_id())).unwrap_json();
+    let available = balance.available.0;
+
+    // ...and no longer needs to guess deposits when approving
+    call!(root, nft.nft_approve(TOKEN_ID.into(), alice.valid_account_id(), None), deposit = 1)
+        .assert_success();
+    call!(
+        root,
+        nft.nft_approve(TOKEN_ID.into(), token_receiver.valid_account_id(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    let token: Token = view!(nft.nft_token(TOKEN_ID.into())).unwrap_json();
+    assert_eq!(token.approved_account_ids.unwrap().len(), 2);
+
+    let balance: StorageBalance =
+        view!(nft.storage_balance_of(root.valid_account_id())).unwrap_json();
+    assert!(balance.available.0 < available);
+}
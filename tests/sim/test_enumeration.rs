@@ -1,31 +1,13 @@
 use crate::utils::{helper_mint, init};
 use near_contract_standards::non_fungible_token::Token;
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{U128, U64};
 use near_sdk_sim::{view, ContractAccount, UserAccount};
-use non_fungible_token::ContractContract as NftContract;
+use non_fungible_token::{ContractContract as NftContract, TokenPage};
 
 fn mint_more(root: &UserAccount, nft: &ContractAccount<NftContract>) {
-    helper_mint(
-        "1".to_string(),
-        &root,
-        &nft,
-        "Black as the Night".to_string(),
-        "In charcoal".to_string(),
-    );
-    helper_mint(
-        "2".to_string(),
-        &root,
-        &nft,
-        "Hamakua".to_string(),
-        "Vintage recording".to_string(),
-    );
-    helper_mint(
-        "3".to_string(),
-        &root,
-        &nft,
-        "Aloha ke akua".to_string(),
-        "Original with piano".to_string(),
-    );
+    helper_mint(&root, &nft);
+    helper_mint(&root, &nft);
+    helper_mint(&root, &nft);
 }
 
 #[test]
@@ -45,23 +27,23 @@ fn simulate_enum_nft_tokens() {
     // No optional args should return all
     let mut tokens: Vec<Token> = view!(nft.nft_tokens(None, None)).unwrap_json();
     assert_eq!(tokens.len(), 4);
-    // Start at "1", with no limit arg
+    // Start at index 1, with no limit arg
     tokens = view!(nft.nft_tokens(Some(U128::from(1)), None)).unwrap_json();
     assert_eq!(tokens.len(), 3);
-    assert_eq!(tokens.get(0).unwrap().token_id, "1".to_string());
-    assert_eq!(tokens.get(1).unwrap().token_id, "2".to_string());
-    assert_eq!(tokens.get(2).unwrap().token_id, "3".to_string());
+    assert_eq!(tokens.get(0).unwrap().token_id, "2".to_string());
+    assert_eq!(tokens.get(1).unwrap().token_id, "3".to_string());
+    assert_eq!(tokens.get(2).unwrap().token_id, "4".to_string());
 
-    // Start at "2", with limit 1
+    // Start at index 2, with limit 1
     tokens = view!(nft.nft_tokens(Some(U128::from(2)), Some(1u64))).unwrap_json();
     assert_eq!(tokens.len(), 1);
-    assert_eq!(tokens.get(0).unwrap().token_id, "2".to_string());
+    assert_eq!(tokens.get(0).unwrap().token_id, "3".to_string());
 
     // Don't specify from_index, but limit 2
     tokens = view!(nft.nft_tokens(None, Some(2u64))).unwrap_json();
     assert_eq!(tokens.len(), 2);
-    assert_eq!(tokens.get(0).unwrap().token_id, "0".to_string());
-    assert_eq!(tokens.get(1).unwrap().token_id, "1".to_string());
+    assert_eq!(tokens.get(0).unwrap().token_id, "1".to_string());
+    assert_eq!(tokens.get(1).unwrap().token_id, "2".to_string());
 }
 
 #[test]
@@ -102,21 +84,48 @@ fn simulate_enum_nft_tokens_for_owner() {
         view!(nft.nft_tokens_for_owner(root.valid_account_id(), Some(U128::from(2)), None))
             .unwrap_json();
     assert_eq!(owner_tokens.len(), 2);
-    assert_eq!(owner_tokens.get(0).unwrap().token_id, "2".to_string());
-    assert_eq!(owner_tokens.get(1).unwrap().token_id, "3".to_string());
+    assert_eq!(owner_tokens.get(0).unwrap().token_id, "3".to_string());
+    assert_eq!(owner_tokens.get(1).unwrap().token_id, "4".to_string());
 
     // With from_index and limit 1
     owner_tokens =
         view!(nft.nft_tokens_for_owner(root.valid_account_id(), Some(U128::from(1)), Some(1)))
             .unwrap_json();
     assert_eq!(owner_tokens.len(), 1);
-    assert_eq!(owner_tokens.get(0).unwrap().token_id, "1".to_string());
+    assert_eq!(owner_tokens.get(0).unwrap().token_id, "2".to_string());
 
     // No from_index but limit 3
     owner_tokens =
         view!(nft.nft_tokens_for_owner(root.valid_account_id(), None, Some(3))).unwrap_json();
     assert_eq!(owner_tokens.len(), 3);
-    assert_eq!(owner_tokens.get(0).unwrap().token_id, "0".to_string());
-    assert_eq!(owner_tokens.get(1).unwrap().token_id, "1".to_string());
-    assert_eq!(owner_tokens.get(2).unwrap().token_id, "2".to_string());
+    assert_eq!(owner_tokens.get(0).unwrap().token_id, "1".to_string());
+    assert_eq!(owner_tokens.get(1).unwrap().token_id, "2".to_string());
+    assert_eq!(owner_tokens.get(2).unwrap().token_id, "3".to_string());
+}
+
+#[test]
+fn simulate_enum_nft_tokens_page() {
+    let (root, nft, _, _, _) = init();
+    mint_more(&root, &nft);
+
+    let page: TokenPage = view!(nft.nft_tokens_page(None, Some(3))).unwrap_json();
+    assert_eq!(page.tokens.len(), 3);
+    assert_eq!(page.next_cursor, Some(U64(3)));
+
+    let page: TokenPage = view!(nft.nft_tokens_page(page.next_cursor, Some(3))).unwrap_json();
+    assert_eq!(page.tokens.len(), 1);
+    assert_eq!(page.tokens[0].token_id, "4".to_string());
+    assert_eq!(page.next_cursor, None);
+
+    // variants cycle a, c, e, b
+    let page: TokenPage =
+        view!(nft.nft_tokens_for_variant_page("c".to_string(), None, None)).unwrap_json();
+    assert_eq!(page.tokens.len(), 1);
+    assert_eq!(page.tokens[0].token_id, "2".to_string());
+
+    let page: TokenPage =
+        view!(nft.nft_tokens_for_owner_page(root.valid_account_id(), Some(U64(2)), None))
+            .unwrap_json();
+    assert_eq!(page.tokens.len(), 2);
+    assert_eq!(page.tokens[0].token_id, "3".to_string());
 }
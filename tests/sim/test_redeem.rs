@@ -0,0 +1,55 @@
+use crate::utils::{helper_mint, init, init_pfp_drop, TOKEN_ID};
+use near_contract_standards::non_fungible_token::Token;
+use near_sdk_sim::{call, view};
+use non_fungible_token::Redemption;
+
+#[test]
+fn simulate_redeem() {
+    let (root, nft, alice, _, _) = init();
+    let pfp_drop = init_pfp_drop(&root, &nft);
+
+    call!(root, nft.redeem(TOKEN_ID.into(), alice.valid_account_id()), deposit = 1)
+        .assert_success();
+
+    // the whitelist token is burned...
+    let token: Option<Token> = view!(nft.nft_token(TOKEN_ID.into())).unwrap_json();
+    assert!(token.is_none());
+    let redemption: Option<Redemption> = view!(nft.get_redemption(TOKEN_ID.into())).unwrap_json();
+    let redemption = redemption.unwrap();
+    assert_eq!(redemption.owner_id, root.account_id());
+    assert_eq!(redemption.target_id, alice.account_id());
+
+    // ...and the PFP is claimed for alice
+    let claim: Option<String> = view!(pfp_drop.get_claim(alice.valid_account_id())).unwrap_json();
+    assert_eq!(claim, Some(TOKEN_ID.to_string()));
+}
+
+#[test]
+fn simulate_redeem_rolls_back_on_failed_claim() {
+    let (root, nft, alice, _, _) = init();
+    init_pfp_drop(&root, &nft);
+    helper_mint(&root, &nft);
+
+    call!(root, nft.redeem(TOKEN_ID.into(), alice.valid_account_id()), deposit = 1)
+        .assert_success();
+
+    // alice already claimed, so the PFP drop panics and the token goes back to root
+    call!(root, nft.redeem("2".into(), alice.valid_account_id()), deposit = 1);
+
+    let token: Token = view!(nft.nft_token("2".into())).unwrap_json();
+    assert_eq!(token.owner_id, root.account_id());
+    let redemption: Option<Redemption> = view!(nft.get_redemption("2".into())).unwrap_json();
+    assert!(redemption.is_none());
+}
+
+#[test]
+fn simulate_redeem_requires_token_owner() {
+    let (root, nft, alice, _, _) = init();
+    init_pfp_drop(&root, &nft);
+
+    let outcome = call!(alice, nft.redeem(TOKEN_ID.into(), alice.valid_account_id()), deposit = 1);
+    assert!(!outcome.is_ok());
+
+    let token: Token = view!(nft.nft_token(TOKEN_ID.into())).unwrap_json();
+    assert_eq!(token.owner_id, root.account_id());
+}
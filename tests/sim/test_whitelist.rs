@@ -0,0 +1,44 @@
+use crate::utils::{helper_mint, init, init_whitelist_caller, TOKEN_ID};
+use near_sdk_sim::{call, view};
+use non_fungible_token::WhitelistStatus;
+
+#[test]
+fn simulate_whitelist_status() {
+    let (root, nft, alice, _, _) = init();
+    helper_mint(&root, &nft);
+
+    let status: WhitelistStatus = view!(nft.whitelist_status(root.valid_account_id())).unwrap_json();
+    assert_eq!(status.held, 2);
+    assert_eq!(status.unredeemed, 2);
+    assert_eq!(status.token_ids.len(), 2);
+
+    let status: WhitelistStatus =
+        view!(nft.whitelist_status(alice.valid_account_id())).unwrap_json();
+    assert_eq!(status.held, 0);
+    assert_eq!(status.unredeemed, 0);
+    assert!(status.token_ids.is_empty());
+}
+
+#[test]
+fn simulate_whitelist_status_from_contract() {
+    let (root, nft, alice, _, _) = init();
+    let caller = init_whitelist_caller(&root, &nft);
+
+    // root holds an unredeemed token...
+    let outcome = call!(root, caller.check_whitelist(root.valid_account_id()));
+    assert!(outcome.is_ok());
+    let whitelisted: bool = outcome.unwrap_json();
+    assert!(whitelisted);
+
+    // ...alice doesn't hold any
+    let whitelisted: bool =
+        call!(root, caller.check_whitelist(alice.valid_account_id())).unwrap_json();
+    assert!(!whitelisted);
+
+    // once its only token is marked as redeemed, root is no longer whitelisted
+    call!(nft.user_account, nft.add_redeemer(root.valid_account_id())).assert_success();
+    call!(root, nft.mark_redeemed(TOKEN_ID.into())).assert_success();
+    let whitelisted: bool =
+        call!(root, caller.check_whitelist(root.valid_account_id())).unwrap_json();
+    assert!(!whitelisted);
+}
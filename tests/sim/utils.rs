@@ -1,28 +1,35 @@
 use approval_receiver::ApprovalReceiverContract;
-use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
 use non_fungible_token::ContractContract as NftContract;
+use pfp_drop::PfpDropContract;
 use token_receiver::TokenReceiverContract;
+use whitelist_caller::WhitelistCallerContract;
 
-use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::json_types::ValidAccountId;
 use near_sdk_sim::{call, deploy, init_simulator, to_yocto, ContractAccount, UserAccount};
+use std::convert::TryFrom;
 
 // Load in contract bytes at runtime
 near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
     NFT_WASM_BYTES => "res/non_fungible_token.wasm",
     TOKEN_RECEIVER_WASM_BYTES => "res/token_receiver.wasm",
     APPROVAL_RECEIVER_WASM_BYTES => "res/approval_receiver.wasm",
+    PFP_DROP_WASM_BYTES => "res/pfp_drop.wasm",
+    WHITELIST_CALLER_WASM_BYTES => "res/whitelist_caller.wasm",
 }
 
 const NFT_ID: &str = "nft";
 const TOKEN_RECEIVER_ID: &str = "token-receiver";
 const APPROVAL_RECEIVER_ID: &str = "approval-receiver";
+const PFP_DROP_ID: &str = "pfp-drop";
+const WHITELIST_CALLER_ID: &str = "whitelist-caller";
 
 // TODO: how to export String instead of &str? Way too much `into`/`to_string` with &str.
-pub const TOKEN_ID: &str = "0";
+pub const TOKEN_ID: &str = "1";
 
 /// Initialize simulator and return:
-/// * root: the root user, set as owner_id for NFT contract, owns a token with ID=1
-/// * nft: the NFT contract, callable with `call!` and `view!`
+/// * root: the root user, owns a token with ID=1 minted through `nft_mint`
+/// * nft: the NFT contract, callable with `call!` and `view!`. It is its own owner_id, since
+///   `nft_mint` mints through a call back into the contract.
 /// * alice: a user account, does not yet own any tokens
 /// * token_receiver: a contract implementing `nft_on_transfer` for use with `transfer_and_call`
 pub fn init() -> (
@@ -45,32 +52,11 @@ pub fn init() -> (
         signer_account: root,
         // init method
         init_method: new_default_meta(
-            root.valid_account_id()
+            ValidAccountId::try_from(NFT_ID).unwrap()
         )
     );
 
-    call!(
-        root,
-        nft.nft_mint(
-            TOKEN_ID.into(),
-            root.valid_account_id(),
-            TokenMetadata {
-                title: Some("Olympus Mons".into()),
-                description: Some("The tallest mountain in the charted solar system".into()),
-                media: None,
-                media_hash: None,
-                copies: Some(1u64),
-                issued_at: None,
-                expires_at: None,
-                starts_at: None,
-                updated_at: None,
-                extra: None,
-                reference: None,
-                reference_hash: None,
-            }
-        ),
-        deposit = 7000000000000000000000
-    );
+    helper_mint(&root, &nft);
 
     let alice = root.create_user("alice".to_string(), to_yocto("100"));
 
@@ -97,33 +83,43 @@ pub fn init() -> (
     (root, nft, alice, token_receiver, approval_receiver)
 }
 
-pub fn helper_mint(
-    token_id: TokenId,
+/// Deploy the stub PFP drop and register it as the redemption target of `nft`.
+pub fn init_pfp_drop(
     root: &UserAccount,
     nft: &ContractAccount<NftContract>,
-    title: String,
-    desc: String,
-) {
-    call!(
-        root,
-        nft.nft_mint(
-            token_id,
-            root.valid_account_id(),
-            TokenMetadata {
-                title: Some(title),
-                description: Some(desc),
-                media: None,
-                media_hash: None,
-                copies: Some(1u64),
-                issued_at: None,
-                expires_at: None,
-                starts_at: None,
-                updated_at: None,
-                extra: None,
-                reference: None,
-                reference_hash: None,
-            }
-        ),
-        deposit = 7000000000000000000000
+) -> ContractAccount<PfpDropContract> {
+    let pfp_drop = deploy!(
+        contract: PfpDropContract,
+        contract_id: PFP_DROP_ID,
+        bytes: &PFP_DROP_WASM_BYTES,
+        signer_account: root,
+        init_method: new(
+            nft.valid_account_id()
+        )
     );
+
+    call!(nft.user_account, nft.set_pfp_contract(pfp_drop.valid_account_id())).assert_success();
+
+    pfp_drop
+}
+
+/// Deploy the stub PFP sale contract that checks `whitelist_status` of `nft` through a promise.
+pub fn init_whitelist_caller(
+    root: &UserAccount,
+    nft: &ContractAccount<NftContract>,
+) -> ContractAccount<WhitelistCallerContract> {
+    deploy!(
+        contract: WhitelistCallerContract,
+        contract_id: WHITELIST_CALLER_ID,
+        bytes: &WHITELIST_CALLER_WASM_BYTES,
+        signer_account: root,
+        init_method: new(
+            nft.valid_account_id()
+        )
+    )
+}
+
+/// Mint the next token for `user` by paying the mint price.
+pub fn helper_mint(user: &UserAccount, nft: &ContractAccount<NftContract>) {
+    call!(user, nft.nft_mint(), deposit = to_yocto("5")).assert_success();
 }